@@ -0,0 +1,35 @@
+// Engine throughput: plays whole games of the standard schedule between BasicPlayers and
+// reports games per second. BasicPlayers never lay down, so every hand runs to the turn cap
+// and the time goes to the engine itself: views, ledger checks and registry lookups.
+// Run with `cargo bench --bench games`; results go to stderr.
+use std::time::Instant;
+use rumminator::{ContractSchedule, Player};
+use rumminator::engine::{game, HouseRules};
+use rumminator::test_player::BasicPlayer;
+
+const GAMES: usize = 50;
+const PLAYERS: usize = 4;
+const TURN_CAP: usize = 100;
+
+fn main() {
+    let rules = HouseRules { turn_cap: TURN_CAP, ..HouseRules::default() };
+    let schedule = ContractSchedule::standard();
+    let start = Instant::now();
+    for _ in 0..GAMES {
+        let mut players: Vec<Box<dyn Player>> = (0..PLAYERS)
+            .map(|_| Box::new(BasicPlayer::new()) as Box<dyn Player>)
+            .collect();
+        game(&mut players, &schedule, &rules).expect("game failed");
+    }
+    let elapsed = start.elapsed();
+    
+    eprintln!(
+        "{} games of {} hands, {} players, {} turns a hand: {:.2?} ({:.1} games/s)",
+        GAMES,
+        schedule.len(),
+        PLAYERS,
+        TURN_CAP,
+        elapsed,
+        GAMES as f64 / elapsed.as_secs_f64()
+    );
+}
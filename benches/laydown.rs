@@ -0,0 +1,144 @@
+// Whole games played to the finish: the players lay down as soon as they can and play off
+// their hand onto the table after, so hands end with someone going out. Most of the time goes
+// to the players' solver calls; `games` measures the engine on its own.
+// Run with `cargo bench --bench laydown`; results go to stderr.
+use std::cmp::Reverse;
+use std::time::Instant;
+use anyhow::Result;
+use rumminator::{
+    CardMove, CardView, ContractBid, ContractSchedule, DrawDecision, Player, PlayerView, TableSet, TurnResult,
+};
+use rumminator::card::{CardContainer, ContainerId};
+use rumminator::engine::{game, HouseRules};
+use rumminator::gratuity::find_gratuities;
+use rumminator::solver::contract_distance;
+
+const GAMES: usize = 20;
+const PLAYERS: usize = 4;
+
+/// Lays down the highest-scoring bid it can, then sheds cards onto the table
+#[derive(Debug)]
+struct LayDownPlayer;
+
+impl LayDownPlayer {
+    /// The best bid that leaves a card to discard; exactly one if the contract must be laid
+    /// down going out
+    fn lay_down(&self, view: &PlayerView, hand: &CardContainer) -> Option<ContractBid> {
+        let keep = if view.contract.one_turn_out() { 1..=1 } else { 1..=hand.cards.len() };
+        ContractBid::find_all(hand, &view.contract).into_iter().find(|bid| {
+            let used: usize = bid.groups.iter().chain(&bid.runs).map(|set| set.cards.len()).sum();
+            keep.contains(&(hand.cards.len() - used))
+        })
+    }
+    
+    /// Single-card plays onto the table, as long as a card is left to discard
+    fn shed(&self, view: &PlayerView, hand: &mut CardContainer, moves: &mut Vec<CardMove>) {
+        let mut table: Vec<TableSet> = view.table_groups.iter()
+            .chain(&view.table_runs)
+            .cloned()
+            .collect();
+        while hand.cards.len() > 1 {
+            let Some(play) = find_gratuities(&table, hand).into_iter().next() else {
+                break;
+            };
+            let idx = hand.cards.iter().position(|card| card.id == play.card).unwrap();
+            let card = hand.cards.remove(idx);
+            table.iter_mut().find(|set| set.id == play.target).unwrap().cards.cards.push(card);
+            moves.push(play.to_move());
+        }
+    }
+    
+    /// Before laying down, the card whose loss hurts the hand least; after, the costliest
+    fn discard(&self, view: &PlayerView, hand: &CardContainer, laid_down: bool) -> CardView {
+        let cost = |idx: usize| {
+            let score = Reverse(hand.cards[idx].score_value);
+            if laid_down {
+                return (0, score);
+            }
+            let mut rest = hand.clone();
+            rest.cards.remove(idx);
+            (contract_distance(&rest, &view.contract), score)
+        };
+        let best = (0..hand.cards.len()).min_by_key(|&idx| cost(idx)).unwrap();
+        hand.cards[best].clone()
+    }
+}
+
+impl Player for LayDownPlayer {
+    fn check_nunu(&mut self, _view: &PlayerView, _discarded_card: &CardView) -> bool {
+        false
+    }
+    
+    fn draw_decision(&mut self, view: &PlayerView) -> DrawDecision {
+        let Some(discard) = &view.last_discard else {
+            return DrawDecision::Deck;
+        };
+        if view.players_laid_down[0] {
+            return DrawDecision::Deck;
+        }
+        let mut with_discard = view.held_cards.clone();
+        with_discard.cards.push(discard.clone());
+        let before = contract_distance(&view.held_cards, &view.contract);
+        if contract_distance(&with_discard, &view.contract) < before {
+            DrawDecision::Discard
+        } else {
+            DrawDecision::Deck
+        }
+    }
+    
+    fn play_turn(&mut self, view: &PlayerView) -> Result<TurnResult> {
+        let mut hand = view.held_cards.clone();
+        let mut moves = Vec::new();
+        let mut laid_down = view.players_laid_down[0];
+        
+        if laid_down {
+            if !view.contract.one_turn_out() {
+                self.shed(view, &mut hand, &mut moves);
+            }
+        } else if let Some(bid) = self.lay_down(view, &hand) {
+            let groups = bid.groups.iter().enumerate()
+                .map(|(idx, set)| (ContainerId::Group(view.seat, idx), set));
+            let runs = bid.runs.iter().enumerate()
+                .map(|(idx, set)| (ContainerId::Run(view.seat, idx), set));
+            for (target, set) in groups.chain(runs) {
+                for card in &set.cards {
+                    moves.push(CardMove { card_id: card.id, to_container: target });
+                    hand.cards.retain(|held| held.id != card.id);
+                }
+            }
+            laid_down = true;
+        }
+        
+        let discard = self.discard(view, &hand, laid_down);
+        Ok(TurnResult { move_ledger: moves, discard: discard.id })
+    }
+    
+    fn notify_game_update(&mut self, _view: &PlayerView) {}
+}
+
+fn main() {
+    let rules = HouseRules::default();
+    let schedule = ContractSchedule::standard();
+    let mut hands = 0;
+    let mut won = 0;
+    let start = Instant::now();
+    for _ in 0..GAMES {
+        let mut players: Vec<Box<dyn Player>> = (0..PLAYERS)
+            .map(|_| Box::new(LayDownPlayer) as Box<dyn Player>)
+            .collect();
+        let scoresheet = game(&mut players, &schedule, &rules).expect("game failed");
+        hands += scoresheet.hands().len();
+        won += scoresheet.hands().iter().filter(|outcome| outcome.winner.is_some()).count();
+    }
+    let elapsed = start.elapsed();
+    
+    eprintln!(
+        "{} games, {} players: {} hands ({} won) in {:.2?} ({:.1} games/s)",
+        GAMES,
+        PLAYERS,
+        hands,
+        won,
+        elapsed,
+        GAMES as f64 / elapsed.as_secs_f64()
+    );
+}
@@ -1,15 +1,28 @@
 use std::collections::HashMap;
+use std::fmt;
 use anyhow::{Result, anyhow};
+use crate::scoring::ScoringTable;
 
 // Core card system with unique IDs
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct CardId(u16); // 3-digit ID: 011 = Ace of Spades (deck 1), 012 = Ace of Spades (deck 2)
+pub struct CardId(u16); // ID = deck * 100 + base card: 101 = Ace of Hearts (deck 1), 201 = Ace of Hearts (deck 2)
 
 impl CardId {
+    /// Builds a known-good card ID. Panics if the base card or deck is out of range;
+    /// use [`CardId::try_new`] or `CardId::try_from(u16)` for untrusted input.
     pub fn new(base_card: u8, deck: u8) -> Self {
-        // Format: DDC where DD=deck (01,02), C=card (1-52)
-        let id = (deck as u16 * 100) + (base_card as u16);
-        CardId(id)
+        Self::try_new(base_card, deck).unwrap_or_else(|e| panic!("{}", e))
+    }
+    
+    pub fn try_new(base_card: u8, deck: u8) -> Result<Self> {
+        // Format: DCC where D=deck (1..=MAX_DECKS), CC=card (1-52, 53-54 jokers)
+        if !(1..=54).contains(&base_card) {
+            return Err(anyhow!("Invalid card base: {}", base_card));
+        }
+        if !(1..=DeckConfig::MAX_DECKS).contains(&deck) {
+            return Err(anyhow!("Invalid deck number: {}", deck));
+        }
+        Ok(CardId(deck as u16 * 100 + base_card as u16))
     }
     
     pub fn deck(&self) -> u8 {
@@ -21,6 +34,22 @@ impl CardId {
     }
 }
 
+impl TryFrom<u16> for CardId {
+    type Error = anyhow::Error;
+    
+    fn try_from(id: u16) -> Result<Self> {
+        let deck = u8::try_from(id / 100).map_err(|_| anyhow!("Invalid card ID: {}", id))?;
+        CardId::try_new((id % 100) as u8, deck)
+            .map_err(|e| anyhow!("Invalid card ID {}: {}", id, e))
+    }
+}
+
+impl From<CardId> for u16 {
+    fn from(id: CardId) -> u16 {
+        id.0
+    }
+}
+
 // View of a card - for rendering and player interaction
 #[derive(Debug, Clone, PartialEq)]
 pub struct CardView {
@@ -30,54 +59,43 @@ pub struct CardView {
     pub score_value: u32,
 }
 
+/// Non-wild ranks in order, Ace low
+pub(crate) const RANKS_BY_VALUE: [Rank; 13] = [
+    Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six, Rank::Seven,
+    Rank::Eight, Rank::Nine, Rank::Ten, Rank::Jack, Rank::Queen, Rank::King,
+];
+
+/// Suits in base card order: hearts are 1-13, diamonds 14-26, clubs 27-39, spades 40-52
+pub(crate) const SUITS_BY_BASE: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Clubs, Suit::Spades];
+
+/// Rank, suit and score of every base card. CardIds can only hold valid bases, so lookups
+/// never fall outside the table. Index 0 is unused. Scores follow [`ScoringTable::STANDARD`].
+const CARD_TABLE: [(Rank, Suit, u32); 55] = build_card_table();
+
+const fn build_card_table() -> [(Rank, Suit, u32); 55] {
+    let joker_score = ScoringTable::STANDARD.rank_points(Rank::Joker);
+    let mut table = [(Rank::Joker, Suit::None, joker_score); 55]; // Bases 53-54 are jokers
+    let mut base = 1;
+    while base <= 52 {
+        let rank = RANKS_BY_VALUE[(base - 1) % 13];
+        let score = ScoringTable::STANDARD.rank_points(rank);
+        table[base] = (rank, SUITS_BY_BASE[(base - 1) / 13], score);
+        base += 1;
+    }
+    table
+}
+
 // Card rendering functions - static lookup from ID to card properties
 pub fn card_id_to_rank(id: CardId) -> Rank {
-    let base = id.base_card();
-    match base {
-        1..=13 => match base {
-            1 => Rank::Ace,
-            2 => Rank::Two,
-            3 => Rank::Three,
-            4 => Rank::Four,
-            5 => Rank::Five,
-            6 => Rank::Six,
-            7 => Rank::Seven,
-            8 => Rank::Eight,
-            9 => Rank::Nine,
-            10 => Rank::Ten,
-            11 => Rank::Jack,
-            12 => Rank::Queen,
-            13 => Rank::King,
-            _ => unreachable!(),
-        },
-        14..=26 => card_id_to_rank(CardId::new(base - 13, 1)), // Diamonds
-        27..=39 => card_id_to_rank(CardId::new(base - 26, 1)), // Clubs
-        40..=52 => card_id_to_rank(CardId::new(base - 39, 1)), // Spades
-        53..=54 => Rank::Joker, // Two jokers
-        _ => panic!("Invalid card base: {}", base),
-    }
+    CARD_TABLE[id.base_card() as usize].0
 }
 
 pub fn card_id_to_suit(id: CardId) -> Suit {
-    let base = id.base_card();
-    match base {
-        1..=13 => Suit::Hearts,
-        14..=26 => Suit::Diamonds,
-        27..=39 => Suit::Clubs,
-        40..=52 => Suit::Spades,
-        53..=54 => Suit::None, // Jokers
-        _ => panic!("Invalid card base: {}", base),
-    }
+    CARD_TABLE[id.base_card() as usize].1
 }
 
 pub fn card_id_to_score(id: CardId) -> u32 {
-    let rank = card_id_to_rank(id);
-    match rank {
-        Rank::Ace | Rank::Two => 20,
-        Rank::Joker => 50,
-        Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
-        _ => rank as u32,
-    }
+    CARD_TABLE[id.base_card() as usize].2
 }
 
 pub fn card_id_to_view(id: CardId) -> CardView {
@@ -97,12 +115,19 @@ pub enum Rank {
     Joker = 14,
 }
 
+impl Rank {
+    /// Jokers and 2s are wild
+    pub fn is_wild(&self) -> bool {
+        matches!(self, Rank::Two | Rank::Joker)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Suit {
     Hearts, Diamonds, Clubs, Spades, None, // None for jokers
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SetType {
     Group,  // Same rank, different suits (min 3 cards)
     Run,    // Sequential ranks, same suit (min 4 cards)
@@ -115,13 +140,143 @@ impl SetType {
             SetType::Run => 4,
         }
     }
-
+    
     pub fn validate(&self, container: &CardContainer) -> bool {
         match self {
             SetType::Group => container.is_valid_group(),
             SetType::Run => container.is_valid_run(),
         }
     }
+    
+    /// Like `validate`, but with a minimum size set by the contract rather than the default
+    pub fn validate_with_min(&self, container: &CardContainer, min_size: usize) -> bool {
+        self.check(container, min_size).is_ok()
+    }
+    
+    /// Validates the container as this set type, saying why it isn't one
+    pub fn check(&self, container: &CardContainer, min_size: usize) -> Result<(), SetError> {
+        let found = container.cards.len();
+        if found < min_size {
+            return Err(SetError::TooSmall { min: min_size, found });
+        }
+        match self {
+            SetType::Group => container.group_error(),
+            SetType::Run => container.run_error(),
+        }.map_or(Ok(()), Err)
+    }
+    
+    /// Every way the wilds in this container can be read as this set type
+    /// (empty if the cards can't form one, regardless of size)
+    pub fn resolve(&self, container: &CardContainer) -> Vec<SetResolution> {
+        match self {
+            SetType::Group => container.resolve_group(),
+            SetType::Run => container.resolve_run(),
+        }
+    }
+}
+
+/// Why a container isn't a valid group or run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetError {
+    TooSmall { min: usize, found: usize },
+    NoNaturalCard,       // Only Jokers: nothing fixes the group's rank or the run's suit
+    MixedRanks,          // Group naturals of more than one rank
+    MixedSuits,          // Run cards (2s included) of more than one suit
+    TooLong,             // Run of more than 13 cards can't avoid wrapping
+    DuplicateRank(Rank), // Run with the same natural card twice
+    NotContiguous,       // Run naturals too far apart for the cards available to join them
+}
+
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SetError::TooSmall { min, found } => write!(f, "needs at least {} cards, has {}", min, found),
+            SetError::NoNaturalCard => write!(f, "has no natural card"),
+            SetError::MixedRanks => write!(f, "mixes ranks"),
+            SetError::MixedSuits => write!(f, "mixes suits"),
+            SetError::TooLong => write!(f, "is longer than 13 cards"),
+            SetError::DuplicateRank(rank) => write!(f, "has more than one natural {:?}", rank),
+            SetError::NotContiguous => write!(f, "is not a contiguous sequence"),
+        }
+    }
+}
+
+impl std::error::Error for SetError {}
+
+/// What a single wild card is standing in for within a set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WildAssignment {
+    pub card: CardId,
+    pub rank: Rank,
+    pub suit: Suit, // None when the set doesn't pin it down (a Joker in a group)
+}
+
+/// One concrete reading of a valid set: what every wild in it stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetResolution {
+    pub wilds: Vec<WildAssignment>,
+    /// Lowest and highest position covered, counting Ace low as 1 and Ace high as 14.
+    /// A group covers just its own rank.
+    pub span: (u8, u8),
+}
+
+/// Rank of a run position (1 and 14 are both Ace)
+fn rank_at_position(pos: u8) -> Rank {
+    RANKS_BY_VALUE[(pos as usize - 1) % 13]
+}
+
+/// Which cards are in play: how many standard decks are shuffled together, and whether each
+/// brings its two Jokers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeckConfig {
+    pub decks: u8,
+    pub jokers: bool,
+}
+
+impl DeckConfig {
+    /// Most decks in one game; each card can then appear at most this many times
+    /// (the limit of `CardMultiset`)
+    pub const MAX_DECKS: u8 = 3;
+    
+    /// Two decks for up to 4 players, three for 5-8
+    pub fn for_players(num_players: usize) -> Self {
+        let decks = if num_players <= 4 { 2 } else { 3 };
+        Self { decks, jokers: true }
+    }
+    
+    /// Base cards (1-54) in each deck
+    pub fn base_cards(&self) -> u8 {
+        if self.jokers { 54 } else { 52 }
+    }
+    
+    pub fn card_count(&self) -> usize {
+        self.decks as usize * self.base_cards() as usize
+    }
+    
+    /// Every card in play under this configuration, deck by deck
+    pub fn cards(&self) -> impl Iterator<Item = CardId> {
+        let base_cards = self.base_cards();
+        (1..=self.decks).flat_map(move |deck| {
+            (1..=base_cards).map(move |base_card| CardId::new(base_card, deck))
+        })
+    }
+    
+    pub fn validate(&self) -> Result<()> {
+        if self.decks == 0 || self.decks > Self::MAX_DECKS {
+            return Err(anyhow!(
+                "Deck count must be between 1 and {}, got {}",
+                Self::MAX_DECKS,
+                self.decks
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for DeckConfig {
+    fn default() -> Self {
+        Self { decks: 2, jokers: true }
+    }
 }
 
 // Container system
@@ -131,107 +286,456 @@ pub struct CardContainer {
 }
 
 impl CardContainer {
-    /// Returns true if this container forms a valid group (3+ same rank, different suits)
+    /// Returns true if this container forms a valid group (3+ same rank, suits may repeat)
     pub fn is_valid_group(&self) -> bool {
-        if self.cards.len() < 3 {
+        if self.cards.len() < SetType::Group.min_size() {
             return false;
         }
         
-        // TODO: Implement group validation logic
-        // All cards must have same rank (accounting for wilds)
-        // Must have at least 3 cards
-        // Can't have duplicate suit+rank combinations (except wilds)
-        true // Placeholder
+        self.group_rank().is_some()
+    }
+    
+    /// The rank this container's cards agree on as a group, if any.
+    ///
+    /// Jokers stand in for anything. A 2 may only stand in for a card of its own suit, but
+    /// groups don't care about suit, so in a group it covers any rank. The rank must be fixed
+    /// by at least one natural card; a group with no naturals other than 2s is a group of 2s.
+    fn group_rank(&self) -> Option<Rank> {
+        let mut rank = None;
+        for card in self.cards.iter().filter(|card| !card.rank.is_wild()) {
+            match rank {
+                None => rank = Some(card.rank),
+                Some(r) if r == card.rank => {}
+                Some(_) => return None, // Two different natural ranks
+            }
+        }
+        
+        rank.or_else(|| {
+            self.cards.iter()
+                .any(|card| card.rank == Rank::Two)
+                .then_some(Rank::Two)
+        })
     }
     
     /// Returns true if this container forms a valid run (4+ sequential same suit)
     pub fn is_valid_run(&self) -> bool {
-        if self.cards.len() < 4 {
+        if self.cards.len() < SetType::Run.min_size() {
             return false;
         }
         
-        // TODO: Implement run validation logic
-        // All cards must be same suit (accounting for wilds)
-        // Must form sequential ranks (accounting for wilds)
-        // Must have at least 4 cards
-        true // Placeholder
+        !self.run_starts().is_empty()
+    }
+    
+    /// Reads this container as a group: which rank each wild stands in for.
+    /// Groups have at most one reading; a natural 2 in a group of 2s isn't a wild.
+    /// Size isn't checked here, since contracts can set their own minimum.
+    pub fn resolve_group(&self) -> Vec<SetResolution> {
+        let Some(rank) = self.group_rank() else {
+            return Vec::new();
+        };
+        
+        let wilds = self.cards.iter()
+            .filter(|card| card.rank.is_wild() && card.rank != rank)
+            .map(|card| WildAssignment { card: card.id, rank, suit: card.suit })
+            .collect();
+        let pos = rank as u8;
+        vec![SetResolution { wilds, span: (pos, pos) }]
+    }
+    
+    /// Reads this container as a run: every distinct placement of its wilds.
+    ///
+    /// Placement is ambiguous when the run could sit at more than one span (a wild at either
+    /// end) or when wilds can swap positions. Identical cards (both Jokers, or the same 2 from
+    /// each deck) are interchangeable, so swapping them doesn't count as a new reading.
+    /// As with groups, size isn't checked.
+    pub fn resolve_run(&self) -> Vec<SetResolution> {
+        let Some(suit) = self.run_suit() else {
+            return Vec::new();
+        };
+        
+        let mut flex: Vec<&CardView> = self.cards.iter()
+            .filter(|card| card.rank.is_wild())
+            .collect();
+        flex.sort_by_key(|card| (card.rank as u8, card.suit as u8));
+        
+        let len = self.cards.len() as u8;
+        let mut resolutions = Vec::new();
+        for start in self.run_starts() {
+            let end = start + len - 1;
+            let open: Vec<u8> = (start..=end)
+                .filter(|&pos| !self.cards.iter().any(|card| {
+                    !card.rank.is_wild() && match card.rank {
+                        Rank::Ace => pos == 1 || pos == 14,
+                        rank => pos == rank as u8,
+                    }
+                }))
+                .collect();
+            
+            let mut placement = Vec::with_capacity(flex.len());
+            place_wilds(&flex, &open, suit, &mut placement, &mut |wilds| {
+                resolutions.push(SetResolution { wilds, span: (start, end) });
+            });
+        }
+        resolutions
+    }
+    
+    /// These cards from the lowest run position to the highest, by the first reading of
+    /// [`CardContainer::resolve_run`]. None if they don't form a run.
+    pub fn in_run_order(&self) -> Option<CardContainer> {
+        let resolution = self.resolve_run().into_iter().next()?;
+        let (low, _) = resolution.span;
+        let position = |card: &CardView| {
+            let rank = resolution.wilds.iter()
+                .find(|wild| wild.card == card.id)
+                .map_or(card.rank, |wild| wild.rank);
+            match rank {
+                Rank::Ace if low == 1 => 1,
+                Rank::Ace => 14,
+                rank => rank as u8,
+            }
+        };
+        
+        let mut cards = self.cards.clone();
+        cards.sort_by_key(position);
+        Some(CardContainer { cards })
+    }
+    
+    /// Why these cards can't form a group, if they can't (size aside)
+    fn group_error(&self) -> Option<SetError> {
+        if self.group_rank().is_some() {
+            return None;
+        }
+        if self.cards.iter().all(|card| card.rank == Rank::Joker) {
+            Some(SetError::NoNaturalCard)
+        } else {
+            Some(SetError::MixedRanks)
+        }
+    }
+    
+    /// Why these cards can't form a run, if they can't (size aside)
+    fn run_error(&self) -> Option<SetError> {
+        if !self.run_starts().is_empty() {
+            return None;
+        }
+        if self.cards.iter().all(|card| card.rank == Rank::Joker) {
+            return Some(SetError::NoNaturalCard);
+        }
+        if self.run_suit().is_none() {
+            return Some(SetError::MixedSuits);
+        }
+        if self.cards.len() > 13 {
+            return Some(SetError::TooLong);
+        }
+        let mut seen = [false; 14];
+        for card in self.cards.iter().filter(|card| !card.rank.is_wild()) {
+            if std::mem::replace(&mut seen[card.rank as usize], true) {
+                return Some(SetError::DuplicateRank(card.rank));
+            }
+        }
+        Some(SetError::NotContiguous)
+    }
+    
+    /// The suit shared by every non-Joker card, if they share one.
+    /// 2s count here too: a 2 can only stand in for a card of its own suit.
+    fn run_suit(&self) -> Option<Suit> {
+        let mut suits = self.cards.iter()
+            .filter(|card| card.rank != Rank::Joker)
+            .map(|card| card.suit);
+        let suit = suits.next()?;
+        suits.all(|s| s == suit).then_some(suit)
+    }
+    
+    /// Every starting position from which these cards can be laid out as one contiguous run.
+    ///
+    /// Positions count Ace low as 1 and Ace high as 14. A run spans at most 13 positions, so it
+    /// can hold the low Ace or the high Ace but never both (no wraparound). Naturals must sit at
+    /// their own position, while Jokers and 2s fill whatever is left; a 2 may also sit at
+    /// position 2 as itself. Card order within the container doesn't matter.
+    fn run_starts(&self) -> Vec<u8> {
+        let len = self.cards.len();
+        if self.run_suit().is_none() || len == 0 || len > 13 {
+            return Vec::new();
+        }
+        
+        let naturals: Vec<Rank> = self.cards.iter()
+            .map(|card| card.rank)
+            .filter(|rank| !rank.is_wild())
+            .collect();
+        
+        let mut starts = Vec::new();
+        for start in 1..=(15 - len as u8) {
+            let end = start + len as u8 - 1;
+            let mut taken = [false; 15];
+            let fits = naturals.iter().all(|&rank| {
+                let pos = match rank {
+                    Rank::Ace if start == 1 => 1,
+                    Rank::Ace => 14,
+                    _ => rank as u8,
+                };
+                let free = (start..=end).contains(&pos) && !taken[pos as usize];
+                taken[pos as usize] = true;
+                free
+            });
+            if fits {
+                starts.push(start);
+            }
+        }
+        starts
+    }
+}
+
+/// Assigns each remaining wild to a distinct open position, reporting every complete placement.
+/// Identical cards are kept in increasing position order so their swaps aren't repeated.
+fn place_wilds(
+    flex: &[&CardView],
+    open: &[u8],
+    suit: Suit,
+    placement: &mut Vec<(usize, u8)>,
+    report: &mut dyn FnMut(Vec<WildAssignment>),
+) {
+    let idx = placement.len();
+    if idx == flex.len() {
+        let wilds = placement.iter()
+            .filter(|&&(i, pos)| !(flex[i].rank == Rank::Two && pos == 2)) // A natural 2 as itself
+            .map(|&(i, pos)| WildAssignment { card: flex[i].id, rank: rank_at_position(pos), suit })
+            .collect();
+        report(wilds);
+        return;
+    }
+    
+    let card = flex[idx];
+    let floor = match placement.last() {
+        Some(&(prev, pos)) if flex[prev].rank == card.rank && flex[prev].suit == card.suit => pos,
+        _ => 0,
+    };
+    for &pos in open {
+        if pos <= floor || placement.iter().any(|&(_, taken)| taken == pos) {
+            continue;
+        }
+        placement.push((idx, pos));
+        place_wilds(flex, open, suit, placement, report);
+        placement.pop();
     }
 }
 
 // Container registry system - ground truth for card locations
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ContainerId(pub String);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContainerId {
+    Deck,
+    Discard,
+    TableTemp,             // Staging area for table manipulation; must be empty at end of turn
+    Hand(usize),           // Player seat
+    Group(usize, usize),   // Player seat, group index
+    Run(usize, usize),     // Player seat, run index
+}
 
 impl ContainerId {
-    pub fn deck() -> Self { ContainerId("deck".to_string()) }
-    pub fn discard() -> Self { ContainerId("discard".to_string()) }
-    pub fn table_temp() -> Self { ContainerId("table_temp".to_string()) }
-    
-    pub fn player_hand(player: usize) -> Self {
-        ContainerId(format!("player_{}_hand", player))
+    /// Seat of the player this container belongs to, if any
+    pub fn owner(&self) -> Option<usize> {
+        match *self {
+            ContainerId::Deck | ContainerId::Discard | ContainerId::TableTemp => None,
+            ContainerId::Hand(seat) | ContainerId::Group(seat, _) | ContainerId::Run(seat, _) => Some(seat),
+        }
     }
     
-    pub fn player_group(player: usize, group_idx: usize) -> Self {
-        ContainerId(format!("player_{}_group_{}", player, group_idx))
+    /// True for containers holding a laid-down group or run
+    pub fn is_table_set(&self) -> bool {
+        matches!(self, ContainerId::Group(..) | ContainerId::Run(..))
     }
     
-    pub fn player_run(player: usize, run_idx: usize) -> Self {
-        ContainerId(format!("player_{}_run_{}", player, run_idx))
+    /// The kind of set this container holds, for table set containers
+    pub fn set_type(&self) -> Option<SetType> {
+        match self {
+            ContainerId::Group(..) => Some(SetType::Group),
+            ContainerId::Run(..) => Some(SetType::Run),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ContainerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContainerId::Deck => write!(f, "deck"),
+            ContainerId::Discard => write!(f, "discard"),
+            ContainerId::TableTemp => write!(f, "table_temp"),
+            ContainerId::Hand(seat) => write!(f, "player_{}_hand", seat),
+            ContainerId::Group(seat, idx) => write!(f, "player_{}_group_{}", seat, idx),
+            ContainerId::Run(seat, idx) => write!(f, "player_{}_run_{}", seat, idx),
+        }
     }
 }
 
-#[derive(Debug)]
+/// Ground truth for card locations.
+///
+/// Keeps both directions: each card's container, and each container's cards. Every container
+/// is an ordered pile: cards sit in the order they arrived, so the last card moved into the
+/// deck or discard is its top, and a run lists its cards in the order laid.
+#[derive(Debug, Clone)]
 pub struct CardRegistry {
     card_locations: HashMap<CardId, ContainerId>,
+    container_cards: HashMap<ContainerId, Vec<CardId>>, // Bottom to top
+}
+
+impl Default for CardRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CardRegistry {
     pub fn new() -> Self {
         Self {
             card_locations: HashMap::new(),
+            container_cards: HashMap::new(),
         }
     }
     
     /// Initialize registry with all cards in deck
-    pub fn initialize_with_deck(&mut self) -> Vec<CardId> {
-        let mut all_cards = Vec::new();
-        
-        // Create 2 decks (52 cards + 2 jokers each = 108 total)
-        for deck in 1..=2 {
-            for base_card in 1..=54 {
-                let card_id = CardId::new(base_card, deck);
-                self.card_locations.insert(card_id, ContainerId::deck());
-                all_cards.push(card_id);
-            }
+    pub fn initialize_with_deck(&mut self, config: &DeckConfig) -> Result<Vec<CardId>> {
+        config.validate()?;
+        
+        // Standard game: 2 decks (52 cards + 2 jokers each = 108 total)
+        let all_cards: Vec<CardId> = config.cards().collect();
+        for &card_id in &all_cards {
+            self.card_locations.insert(card_id, ContainerId::Deck);
         }
+        self.container_cards.insert(ContainerId::Deck, all_cards.clone());
         
-        all_cards
+        Ok(all_cards)
     }
     
+    /// Moves a card onto the top (end) of a container
     pub fn move_card(&mut self, card_id: CardId, to_container: ContainerId) -> Result<()> {
-        if self.card_locations.contains_key(&card_id) {
-            self.card_locations.insert(card_id, to_container);
-            Ok(())
-        } else {
-            Err(anyhow!("Card {:?} not found in registry", card_id))
+        let Some(location) = self.card_locations.get_mut(&card_id) else {
+            return Err(anyhow!("Card {} not found in registry", card_id));
+        };
+        let from_container = std::mem::replace(location, to_container);
+        
+        if let Some(cards) = self.container_cards.get_mut(&from_container) {
+            if let Some(idx) = cards.iter().rposition(|&card| card == card_id) {
+                cards.remove(idx);
+            }
         }
+        self.container_cards.entry(to_container).or_default().push(card_id);
+        Ok(())
     }
     
     pub fn get_location(&self, card_id: CardId) -> Option<&ContainerId> {
         self.card_locations.get(&card_id)
     }
     
-    pub fn get_cards_in_container(&self, container: &ContainerId) -> Vec<CardId> {
-        self.card_locations
-            .iter()
-            .filter(|(_, loc)| *loc == container)
-            .map(|(card, _)| *card)
-            .collect()
+    /// Cards in a container, bottom to top: the order they were moved in.
+    /// Only the deck and discard pile give that order a meaning; for a run in position order,
+    /// use [`CardRegistry::get_container_view`].
+    pub fn get_cards_in_container(&self, container: &ContainerId) -> &[CardId] {
+        self.container_cards.get(container).map_or(&[], Vec::as_slice)
+    }
+    
+    /// The card most recently moved into a container
+    pub fn top_card(&self, container: &ContainerId) -> Option<CardId> {
+        self.get_cards_in_container(container).last().copied()
     }
     
+    /// View of a container's cards, bottom to top. A run that is valid comes back in position
+    /// order instead, lowest first, however its cards arrived.
     pub fn get_container_view(&self, container: &ContainerId) -> CardContainer {
         let card_ids = self.get_cards_in_container(container);
-        let cards = card_ids.into_iter().map(card_id_to_view).collect();
-        CardContainer { cards }
+        let cards = card_ids.iter().copied().map(card_id_to_view).collect();
+        let view = CardContainer { cards };
+        match container {
+            ContainerId::Run(..) => view.in_run_order().unwrap_or(view),
+            _ => view,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn cards(s: &str) -> CardContainer {
+        s.parse().unwrap()
+    }
+    
+    #[test]
+    fn groups_match_ranks_with_wilds() {
+        assert!(cards("4D 4C 4S").is_valid_group());
+        assert!(cards("2S 4D 4C").is_valid_group());
+        assert!(cards("J- J- 4D").is_valid_group());
+        assert!(cards("2S 2D 2C").is_valid_group()); // A group of 2s
+        assert!(!cards("4D 5C 4S").is_valid_group());
+        assert!(!cards("4D 4C").is_valid_group());
+        assert!(!cards("J- J- J-").is_valid_group());
+        assert_eq!(SetType::Group.check(&cards("J- J- J-"), 3), Err(SetError::NoNaturalCard));
+        assert_eq!(SetType::Group.check(&cards("4D 5C 4S"), 3), Err(SetError::MixedRanks));
+    }
+    
+    #[test]
+    fn runs_take_ace_high_or_low_without_wrapping() {
+        assert!(cards("5H 6H 7H 8H").is_valid_run());
+        assert!(cards("8H 5H 7H 6H").is_valid_run()); // Order doesn't matter
+        assert!(cards("AH 2H 3H 4H").is_valid_run()); // A natural 2
+        assert!(cards("JH QH KH AH").is_valid_run());
+        assert!(cards("5H 6H J- 8H").is_valid_run());
+        assert!(cards("5H 6H 2H 8H").is_valid_run());
+        assert!(!cards("KH AH 2H 3H").is_valid_run());
+        assert!(!cards("QH KH AH 2H 3H").is_valid_run());
+        assert!(!cards("5H 6H 7H").is_valid_run());
+        assert!(!cards("J- J- J- J-").is_valid_run());
+        assert!(!cards("5H 6H 2S 8H").is_valid_run()); // A 2 only stands in for its own suit
+        
+        let check = |s: &str| SetType::Run.check(&cards(s), 4);
+        assert_eq!(check("J- J- J- J-"), Err(SetError::NoNaturalCard));
+        assert_eq!(check("5H 6H 7H 8S"), Err(SetError::MixedSuits));
+        assert_eq!(check("5H 5H 6H 7H"), Err(SetError::DuplicateRank(Rank::Five)));
+        assert_eq!(check("5H 6H 7H 9H"), Err(SetError::NotContiguous));
+    }
+    
+    #[test]
+    fn wilds_resolve_to_the_cards_they_stand_for() {
+        let run = cards("9D 2D JD QD KD");
+        let two = run.cards[1].id;
+        assert_eq!(run.resolve_run(), vec![SetResolution {
+            wilds: vec![WildAssignment { card: two, rank: Rank::Ten, suit: Suit::Diamonds }],
+            span: (9, 13),
+        }]);
+        
+        let group = cards("2S 4D 4C");
+        let two = group.cards[0].id;
+        assert_eq!(group.resolve_group(), vec![SetResolution {
+            wilds: vec![WildAssignment { card: two, rank: Rank::Four, suit: Suit::Spades }],
+            span: (4, 4),
+        }]);
+        
+        // A natural 2 isn't a wild, in a run or a group of 2s
+        assert!(cards("AH 2H 3H 4H").resolve_run().iter().all(|r| r.wilds.is_empty()));
+        assert!(cards("2S 2D 2C").resolve_group()[0].wilds.is_empty());
+        
+        // A wild on the end can sit at either end; identical Jokers don't add readings
+        let spans: Vec<(u8, u8)> = cards("5H 6H 7H J-").resolve_run().iter().map(|r| r.span).collect();
+        assert_eq!(spans, vec![(4, 7), (5, 8)]);
+        assert_eq!(cards("5H 6H J- J-").resolve_run().len(), 3);
+        
+        assert!(cards("J- J- J- J-").resolve_run().is_empty());
+        assert!(cards("J- J- J-").resolve_group().is_empty());
+    }
+    
+    #[test]
+    fn run_views_are_in_position_order() {
+        let mut registry = CardRegistry::new();
+        registry.initialize_with_deck(&DeckConfig::for_players(4)).unwrap();
+        let run = ContainerId::Run(0, 0);
+        let group = ContainerId::Group(0, 0);
+        for card in cards("5H 6H 7H 8H 4H JH 9C 9D").cards {
+            let to = if card.suit == Suit::Hearts { run } else { group };
+            registry.move_card(card.id, to).unwrap();
+        }
+        for card in cards("J- 10H KH QH AH").cards {
+            registry.move_card(card.id, run).unwrap();
+        }
+        
+        assert_eq!(registry.get_container_view(&run).to_string(), "4H 5H 6H 7H 8H J- 10H JH QH KH AH");
+        assert_eq!(registry.get_container_view(&group).to_string(), "9C 9D");
     }
-}
\ No newline at end of file
+}
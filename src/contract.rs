@@ -1,69 +1,252 @@
-use crate::card::CardContainer;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use crate::card::{CardContainer, CardId, SetError, SetType};
+use crate::solver;
 
-/// Contract types for each hand
-#[derive(Debug, Clone, PartialEq)]
-pub enum ContractOrder {
-    GG,           // Hand 1: GG
-    GR,         // Hand 2: GR  
-    RR,             // Hand 3: RR
-    GGG,         // Hand 4: GGG
-    GGR,     // Hand 5: GGR
-    GRR,     // Hand 6: GRR
-    RRR,           // Hand 7: RRR
+/// What a hand asks each player to lay down: how many groups and runs, the size of the
+/// dealt hand, how small a group or run may be, and whether laying down must also go out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractOrder {
+    groups: usize,
+    runs: usize,
+    hand_size: usize,
+    min_group_size: usize,
+    min_run_size: usize,
+    one_turn_out: bool,
 }
 
 impl ContractOrder {
-    pub fn from_hand_number(hand_number: usize) -> Self {
-        match hand_number {
-            1 => ContractOrder::GG,
-            2 => ContractOrder::GR,
-            3 => ContractOrder::RR,
-            4 => ContractOrder::GGG,
-            5 => ContractOrder::GGR,
-            6 => ContractOrder::GRR,
-            7 => ContractOrder::RRR,
-            _ => panic!("Invalid hand number: {}", hand_number),
-        }
+    // Standard contract progression (see WHAT.md)
+    pub const GG: ContractOrder = ContractOrder::new(2, 0, 10);   // Hand 1
+    pub const GR: ContractOrder = ContractOrder::new(1, 1, 10);   // Hand 2
+    pub const RR: ContractOrder = ContractOrder::new(0, 2, 10);   // Hand 3
+    pub const GGG: ContractOrder = ContractOrder::new(3, 0, 10);  // Hand 4
+    pub const GGR: ContractOrder = ContractOrder::new(2, 1, 12);  // Hand 5
+    pub const GRR: ContractOrder = ContractOrder::new(1, 2, 12);  // Hand 6
+    pub const RRR: ContractOrder = ContractOrder::new(0, 3, 12).with_one_turn_out(true); // Hand 7
+    
+    /// A contract with the default minimum set sizes (3-card groups, 4-card runs)
+    pub const fn new(groups: usize, runs: usize, hand_size: usize) -> Self {
+        Self { groups, runs, hand_size, min_group_size: 3, min_run_size: 4, one_turn_out: false }
+    }
+    
+    pub const fn with_min_sizes(self, min_group_size: usize, min_run_size: usize) -> Self {
+        Self { min_group_size, min_run_size, ..self }
+    }
+    
+    /// Sets whether players must lay down and go out in the same turn (WHAT.md's last-hand rule)
+    pub const fn with_one_turn_out(self, one_turn_out: bool) -> Self {
+        Self { one_turn_out, ..self }
+    }
+    
+    /// The contract for a hand of the standard seven-hand game
+    pub fn from_hand_number(hand_number: usize) -> Result<Self> {
+        ContractSchedule::standard().hand(hand_number).cloned()
     }
     
     pub fn hand_size(&self) -> usize {
-        match self {
-            ContractOrder::GG |
-            ContractOrder::GR |
-            ContractOrder::RR |
-            ContractOrder::GGG => 10,
-            
-            ContractOrder::GGR |
-            ContractOrder::GRR |
-            ContractOrder::RRR => 12,
-        }
+        self.hand_size
     }
     
     pub fn required_groups(&self) -> usize {
-        match self {
-            ContractOrder::GG => 2,
-            ContractOrder::GR => 1,
-            ContractOrder::RR => 0,
-            ContractOrder::GGG => 3,
-            ContractOrder::GGR => 2,
-            ContractOrder::GRR => 1,
-            ContractOrder::RRR => 0,
-        }
+        self.groups
     }
     
     pub fn required_runs(&self) -> usize {
+        self.runs
+    }
+    
+    /// True if laying down must empty the hand (bar the discard), with no gratuity ever allowed
+    pub fn one_turn_out(&self) -> bool {
+        self.one_turn_out
+    }
+    
+    pub fn min_set_size(&self, set_type: &SetType) -> usize {
+        match set_type {
+            SetType::Group => self.min_group_size,
+            SetType::Run => self.min_run_size,
+        }
+    }
+    
+    /// True if the container is a valid set of this type under this contract's minimum sizes
+    pub fn validate_set(&self, set_type: &SetType, container: &CardContainer) -> bool {
+        set_type.validate_with_min(container, self.min_set_size(set_type))
+    }
+}
+
+/// Compact form: one letter per set, then the hand size, e.g. "GGR:12".
+/// Non-default minimum set sizes follow as `":<min group>:<min run>"`, e.g. "GG:10:4:5", and a
+/// trailing "!" marks a contract that must be laid down going out, e.g. "RRR:12!".
+impl fmt::Display for ContractOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}:{}", "G".repeat(self.groups), "R".repeat(self.runs), self.hand_size)?;
+        let default = ContractOrder::new(self.groups, self.runs, self.hand_size);
+        if (self.min_group_size, self.min_run_size) != (default.min_group_size, default.min_run_size) {
+            write!(f, ":{}:{}", self.min_group_size, self.min_run_size)?;
+        }
+        if self.one_turn_out {
+            write!(f, "!")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ContractOrder {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        let (body, one_turn_out) = match s.trim().strip_suffix('!') {
+            Some(body) => (body, true),
+            None => (s.trim(), false),
+        };
+        let fields: Vec<&str> = body.split(':').map(str::trim).collect();
+        let number = |field: &str| {
+            field.parse::<usize>().map_err(|_| anyhow!("Invalid number {:?} in contract {:?}", field, s))
+        };
+        
+        let (sets, hand_size, min_sizes) = match fields.as_slice() {
+            [sets, hand_size] => (sets, number(hand_size)?, None),
+            [sets, hand_size, group, run] => (sets, number(hand_size)?, Some((number(group)?, number(run)?))),
+            _ => return Err(anyhow!("Contract {:?} should look like \"GGR:12\" or \"GGR:12:3:4\" (\"!\" optional)", s)),
+        };
+        
+        let mut contract = ContractOrder::new(0, 0, hand_size);
+        for set in sets.chars() {
+            match set.to_ascii_uppercase() {
+                'G' => contract.groups += 1,
+                'R' => contract.runs += 1,
+                _ => return Err(anyhow!("Unknown set type {:?} in contract {:?}", set, s)),
+            }
+        }
+        if contract.groups + contract.runs == 0 {
+            return Err(anyhow!("Contract {:?} asks for no sets", s));
+        }
+        if hand_size == 0 {
+            return Err(anyhow!("Contract {:?} deals no cards", s));
+        }
+        if let Some((group, run)) = min_sizes {
+            if group == 0 {
+                return Err(anyhow!("Contract {:?} needs groups of at least one card", s));
+            }
+            if !(1..=13).contains(&run) {
+                return Err(anyhow!("Contract {:?} needs runs of 1 to 13 cards, the most a suit holds", s));
+            }
+            contract = contract.with_min_sizes(group, run);
+        }
+        Ok(contract.with_one_turn_out(one_turn_out))
+    }
+}
+
+/// The contracts for each hand of a game, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSchedule {
+    hands: Vec<ContractOrder>,
+}
+
+impl ContractSchedule {
+    pub fn new(hands: Vec<ContractOrder>) -> Self {
+        Self { hands }
+    }
+    
+    /// The seven-hand progression from WHAT.md: GG, GR, RR, GGG, GGR, GRR, RRR
+    pub fn standard() -> Self {
+        Self::new(vec![
+            ContractOrder::GG,
+            ContractOrder::GR,
+            ContractOrder::RR,
+            ContractOrder::GGG,
+            ContractOrder::GGR,
+            ContractOrder::GRR,
+            ContractOrder::RRR,
+        ])
+    }
+    
+    /// Contract for a hand, numbered from 1
+    pub fn hand(&self, hand_number: usize) -> Result<&ContractOrder> {
+        hand_number.checked_sub(1)
+            .and_then(|idx| self.hands.get(idx))
+            .ok_or_else(|| anyhow!(
+                "Invalid hand number: {} (schedule has {} hands)",
+                hand_number,
+                self.hands.len()
+            ))
+    }
+    
+    pub fn len(&self) -> usize {
+        self.hands.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.hands.is_empty()
+    }
+    
+    pub fn iter(&self) -> impl Iterator<Item = &ContractOrder> {
+        self.hands.iter()
+    }
+}
+
+impl Default for ContractSchedule {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Comma-separated contracts, e.g. "GG:10,GR:10,RR:10"
+impl fmt::Display for ContractSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, contract) in self.hands.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", contract)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ContractSchedule {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        let hands = s.split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<ContractOrder>>>()?;
+        Ok(Self::new(hands))
+    }
+}
+
+
+/// Why a bid doesn't satisfy a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BidError {
+    WrongGroupCount { expected: usize, found: usize },
+    WrongRunCount { expected: usize, found: usize },
+    InvalidGroup(usize, SetError), // Index into the bid's groups
+    InvalidRun(usize, SetError),   // Index into the bid's runs
+    DuplicateCard(CardId),         // The same card used twice
+    CardNotInHand(CardId),         // A card the bidder doesn't hold
+}
+
+impl fmt::Display for BidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ContractOrder::GG => 0,
-            ContractOrder::GR => 1,
-            ContractOrder::RR => 2,
-            ContractOrder::GGG => 0,
-            ContractOrder::GGR => 1,
-            ContractOrder::GRR => 2,
-            ContractOrder::RRR => 3,
+            BidError::WrongGroupCount { expected, found } => {
+                write!(f, "contract needs {} group(s), bid has {}", expected, found)
+            }
+            BidError::WrongRunCount { expected, found } => {
+                write!(f, "contract needs {} run(s), bid has {}", expected, found)
+            }
+            BidError::InvalidGroup(idx, reason) => write!(f, "group {} {}", idx, reason),
+            BidError::InvalidRun(idx, reason) => write!(f, "run {} {}", idx, reason),
+            BidError::DuplicateCard(card) => write!(f, "card {} is used more than once", card),
+            BidError::CardNotInHand(card) => write!(f, "card {} is not in the bidder's hand", card),
         }
     }
 }
 
+impl std::error::Error for BidError {}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ContractBid {
@@ -76,28 +259,76 @@ impl ContractBid {
         Self { groups, runs }
     }
     
-    /// Validates if this bid satisfies the given contract order
-    pub fn validate(&self, contract: &ContractOrder) -> bool {
+    /// Every way the hand can fulfil the contract, including bids that use wilds.
+    /// Bids that shed the most points come first.
+    pub fn find_all(hand: &CardContainer, contract: &ContractOrder) -> Vec<ContractBid> {
+        solver::find_bids(hand, contract)
+    }
+    
+    /// Total score of the cards in this bid: the points it sheds from the hand
+    pub fn points(&self) -> u32 {
+        self.groups.iter()
+            .chain(&self.runs)
+            .flat_map(|set| &set.cards)
+            .map(|card| card.score_value)
+            .sum()
+    }
+    
+    /// Checks that this bid satisfies the given contract order using only cards from `hand`.
+    /// On failure, says why: the first problem found, in the order the checks are listed.
+    pub fn validate(&self, contract: &ContractOrder, hand: &CardContainer) -> Result<(), BidError> {
         // Check correct number of groups and runs
-        if self.groups.len() != contract.required_groups() ||
-           self.runs.len() != contract.required_runs() {
-            return false;
+        if self.groups.len() != contract.required_groups() {
+            return Err(BidError::WrongGroupCount {
+                expected: contract.required_groups(),
+                found: self.groups.len(),
+            });
+        }
+        if self.runs.len() != contract.required_runs() {
+            return Err(BidError::WrongRunCount {
+                expected: contract.required_runs(),
+                found: self.runs.len(),
+            });
         }
         
-        // Validate each group is actually a valid group
-        for group in &self.groups {
-            if !group.is_valid_group() {
-                return false;
+        // Every card is used once, and came from the bidder's hand
+        let mut used = HashSet::new();
+        for card in self.groups.iter().chain(&self.runs).flat_map(|set| &set.cards) {
+            if !used.insert(card.id) {
+                return Err(BidError::DuplicateCard(card.id));
+            }
+            if !hand.cards.iter().any(|held| held.id == card.id) {
+                return Err(BidError::CardNotInHand(card.id));
             }
         }
         
+        // Validate each group is actually a valid group
+        for (idx, group) in self.groups.iter().enumerate() {
+            SetType::Group.check(group, contract.min_set_size(&SetType::Group))
+                .map_err(|reason| BidError::InvalidGroup(idx, reason))?;
+        }
+        
         // Validate each run is actually a valid run
-        for run in &self.runs {
-            if !run.is_valid_run() {
-                return false;
-            }
+        for (idx, run) in self.runs.iter().enumerate() {
+            SetType::Run.check(run, contract.min_set_size(&SetType::Run))
+                .map_err(|reason| BidError::InvalidRun(idx, reason))?;
         }
         
-        true
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn compact_form_round_trips_and_rejects_impossible_contracts() {
+        for s in ["GG:10", "GRR:12", "RRR:12!", "GG:10:4:5", "R:13:3:13"] {
+            assert_eq!(s.parse::<ContractOrder>().unwrap().to_string(), s);
+        }
+        for s in ["GG:0", "GG:10:0:4", "GR:10:3:0", "GR:10:3:14", ":10", "GX:10", "GG"] {
+            assert!(s.parse::<ContractOrder>().is_err(), "{}", s);
+        }
+    }
+}
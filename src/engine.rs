@@ -2,136 +2,311 @@ use anyhow::{Result, anyhow};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use crate::{
-    GameState, ContractOrder, Player, PlayerView, DrawDecision, 
-    CardMove, CardRegistry, ContainerId, CardId, CardView, CardContainer
+    GameState, ContractOrder, ContractSchedule, ContractBid, Player, PlayerView, DrawDecision, 
+    CardMove, TurnResult, CardRegistry, ContainerId, CardId, CardView, CardContainer, DeckConfig, TableSet, SetType,
+    ScoringTable, HandOutcome, Scoresheet, NunuReport
 };
-use crate::card::{card_id_to_view, Rank, Suit};
+use crate::card::card_id_to_view;
 
 #[derive(Debug)]
 enum TurnOutcome {
     Continue,
     HandEnd,
+    Stalemate, // Nothing left to draw
 }
 
-pub fn hand(mut players: Vec<Box<dyn Player>>, hand_number: usize) -> Result<()> {
-    let mut state = initialize_game(players.len(), hand_number)?;
-    
-    // TODO: For now just run a few turns to test the system
-    for _turn in 0..5 {
-        match run_turn(&mut players, &mut state)? {
+/// Table variations that change how hands are played or scored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HouseRules {
+    pub scoring: ScoringTable,
+    /// Turns after which a hand nobody has gone out of ends in a stalemate, fully scored
+    pub turn_cap: usize,
+    /// Cards to deal from; by default, enough decks for the number of players
+    pub deck: Option<DeckConfig>,
+}
+
+impl Default for HouseRules {
+    fn default() -> Self {
+        Self {
+            scoring: ScoringTable::default(),
+            turn_cap: 1000,
+            deck: None,
+        }
+    }
+}
+
+/// Plays every hand of the schedule with the same players, returning the scoresheet.
+/// The deal passes left each hand, starting with seat 0.
+pub fn game(players: &mut [Box<dyn Player>], schedule: &ContractSchedule, rules: &HouseRules) -> Result<Scoresheet> {
+    require_players(players)?;
+    
+    let mut scoresheet = Scoresheet::new(players.len());
+    for (idx, contract) in schedule.iter().enumerate() {
+        let dealer = idx % players.len();
+        let outcome = play_hand(players, contract, dealer, rules)?;
+        scoresheet.record(outcome)?;
+    }
+    Ok(scoresheet)
+}
+
+/// Plays one hand of the standard schedule, dealt as it would be in a full game
+pub fn hand(players: &mut [Box<dyn Player>], hand_number: usize, rules: &HouseRules) -> Result<HandOutcome> {
+    require_players(players)?;
+    let contract = ContractSchedule::standard().hand(hand_number)?.clone();
+    let dealer = (hand_number - 1) % players.len();
+    play_hand(players, &contract, dealer, rules)
+}
+
+fn require_players(players: &[Box<dyn Player>]) -> Result<()> {
+    if players.is_empty() {
+        return Err(anyhow!("A game needs at least one player"));
+    }
+    Ok(())
+}
+
+/// Deals and plays a hand; the player to the dealer's left goes first. A hand nobody goes out
+/// of ends in a stalemate at the turn cap, or when there's no card left to draw.
+fn play_hand(
+    players: &mut [Box<dyn Player>],
+    contract: &ContractOrder,
+    dealer: usize,
+    rules: &HouseRules,
+) -> Result<HandOutcome> {
+    let deck = rules.deck.unwrap_or_else(|| DeckConfig::for_players(players.len()));
+    let first_player = (dealer + 1) % players.len();
+    let mut state = initialize_game(players.len(), first_player, contract.clone(), &deck)?;
+    
+    let mut turns = 0;
+    while turns < rules.turn_cap {
+        turns += 1;
+        match run_turn(players, &mut state)? {
             TurnOutcome::Continue => continue,
             TurnOutcome::HandEnd => {
-                println!("Hand ended!");
-                return Ok(());
+                return Ok(score_hand(&state, Some(state.current_player), turns, &rules.scoring));
             }
+            TurnOutcome::Stalemate => break,
         }
     }
     
-    println!("Completed 5 turns successfully");
-    Ok(())
+    Ok(score_hand(&state, None, turns, &rules.scoring))
 }
 
-fn initialize_game(num_players: usize, hand_number: usize) -> Result<GameState> {
+/// Totals the cards each player still holds; whoever went out holds none
+fn score_hand(state: &GameState, winner: Option<usize>, turns: usize, scoring: &ScoringTable) -> HandOutcome {
+    let points = (0..state.num_players)
+        .map(|seat| scoring.hand_points(state.card_registry.get_cards_in_container(&ContainerId::Hand(seat))))
+        .collect();
+    
+    HandOutcome {
+        winner,
+        points,
+        turns,
+        laid_down: state.players_laid_down.clone(),
+    }
+}
+
+fn initialize_game(
+    num_players: usize,
+    first_player: usize,
+    contract: ContractOrder,
+    deck: &DeckConfig,
+) -> Result<GameState> {
+    let hand_size = contract.hand_size();
+    
+    // Every player needs a full hand, plus one card to start the discard pile
+    let cards_needed = num_players * hand_size + 1;
+    if deck.card_count() < cards_needed {
+        return Err(anyhow!(
+            "Not enough cards to deal: {} players x {} cards plus a discard needs {}, but {} deck(s) hold {}",
+            num_players,
+            hand_size,
+            cards_needed,
+            deck.decks,
+            deck.card_count()
+        ));
+    }
+    
     let mut card_registry = CardRegistry::new();
-    let all_cards = card_registry.initialize_with_deck();
+    let all_cards = card_registry.initialize_with_deck(deck)?;
     
     // Shuffle the card IDs
     let mut shuffled_cards = all_cards;
     shuffled_cards.shuffle(&mut thread_rng());
     
-    let contract = ContractOrder::from_hand_number(hand_number);
-    let hand_size = contract.hand_size();
-    
     // Deal cards to player hands
     let mut card_idx = 0;
     for player_id in 0..num_players {
         for _ in 0..hand_size {
-            if card_idx >= shuffled_cards.len() {
-                return Err(anyhow!("Not enough cards to deal"));
-            }
             card_registry.move_card(
                 shuffled_cards[card_idx], 
-                ContainerId::player_hand(player_id)
+                ContainerId::Hand(player_id)
             )?;
             card_idx += 1;
         }
     }
     
     // First discard
-    if card_idx >= shuffled_cards.len() {
-        return Err(anyhow!("No cards left for initial discard"));
-    }
-    card_registry.move_card(shuffled_cards[card_idx], ContainerId::discard())?;
+    card_registry.move_card(shuffled_cards[card_idx], ContainerId::Discard)?;
     card_idx += 1;
     
-    // Rest go to deck
+    // Rest go to deck, in shuffled order: the last card moved in is the top
     for &card_id in &shuffled_cards[card_idx..] {
-        card_registry.move_card(card_id, ContainerId::deck())?;
+        card_registry.move_card(card_id, ContainerId::Deck)?;
     }
     
     Ok(GameState {
         card_registry,
-        current_player: 0,
+        num_players,
+        current_player: first_player,
         contract,
         players_laid_down: vec![false; num_players],
+        discard_live: true, // The first player may take the card turned up at the deal
+        last_discarder: None,
+        last_nunu: None,
     })
 }
 
 fn run_turn(players: &mut [Box<dyn Player>], state: &mut GameState) -> Result<TurnOutcome> {
     let num_players = players.len();
     
-    println!("Turn {}: Player {} playing", state.current_player, state.current_player);
+    // 1. Poll for nunu: the active player claiming the discard takes it as their draw
+    let drew_by_nunu = poll_nunu(players, state)?;
     
-    // 1. TODO: Poll for nunu - simplified for now
-    
-    // 2. Active player decides draw source
-    let view = generate_view(state, state.current_player);
-    let draw_decision = players[state.current_player].draw_decision(&view);
-    execute_draw(state, draw_decision)?;
+    // 2. Otherwise, active player decides draw source
+    if !drew_by_nunu {
+        let view = generate_view(state, state.current_player);
+        let draw_decision = players[state.current_player].draw_decision(&view);
+        if !execute_draw(state, draw_decision)? {
+            return Ok(TurnOutcome::Stalemate);
+        }
+    }
     
     // 3. Active player plays their turn
     let updated_view = generate_view(state, state.current_player);
     let turn_result = players[state.current_player].play_turn(&updated_view)?;
     
-    // 4. Validate and execute move ledger
-    validate_move_ledger(state, &turn_result.move_ledger)?;
-    execute_move_ledger(state, turn_result.move_ledger)?;
+    // 4. Validate move ledger, then stage it and the discard on a copy of the registry
+    validate_move_ledger(state, &turn_result.move_ledger, turn_result.discard)?;
+    let staged = stage_turn(&state.card_registry, &turn_result)?;
+    
+    // 5. Validate the end of turn: temp container empty, any lay-down fulfils the contract,
+    //    and every bid on the table is valid
+    validate_temp_container_end_state(&staged)?;
+    let lays_down = validate_lay_down(state, &staged)?;
+    validate_table_end_state(state, &staged, lays_down)?;
     
-    // 5. Execute discard
-    execute_discard(state, turn_result.discard)?;
+    // 6. Commit the turn
+    state.card_registry = staged;
+    state.players_laid_down[state.current_player] |= lays_down;
+    state.discard_live = true;
+    state.last_discarder = Some(state.current_player);
     
-    // 6. Check for hand end
+    // 7. Check for hand end
     if is_hand_over(state) {
         return Ok(TurnOutcome::HandEnd);
     }
     
-    // 7. Advance to next player
+    // 8. Advance to next player
     state.current_player = (state.current_player + 1) % num_players;
     
     Ok(TurnOutcome::Continue)
 }
 
-fn execute_draw(state: &mut GameState, decision: DrawDecision) -> Result<()> {
-    let card_id = match decision {
-        DrawDecision::Deck => {
-            let deck_cards = state.card_registry.get_cards_in_container(&ContainerId::deck());
-            *deck_cards.last().ok_or_else(|| anyhow!("Deck is empty"))?
+/// Offers the live discard to every player but its discarder, in seat order from the
+/// discarder's left. The first to ask gets it; anyone but the active player also takes a
+/// penalty card from the deck, if there is one. Returns true if the active player claimed it as their draw.
+fn poll_nunu(players: &mut [Box<dyn Player>], state: &mut GameState) -> Result<bool> {
+    let Some(discarder) = state.last_discarder else {
+        return Ok(false);
+    };
+    let Some(card_id) = state.card_registry.top_card(&ContainerId::Discard) else {
+        return Ok(false);
+    };
+    if !state.discard_live {
+        return Ok(false);
+    }
+    
+    let card = card_id_to_view(card_id);
+    let num_players = state.num_players;
+    let asked: Vec<usize> = (1..num_players)
+        .map(|offset| (discarder + offset) % num_players)
+        .filter(|&seat| {
+            let view = generate_view(state, seat);
+            players[seat].check_nunu(&view, &card)
+        })
+        .collect();
+    
+    let claimed_by = asked.first().copied();
+    if let Some(seat) = claimed_by {
+        state.card_registry.move_card(card_id, ContainerId::Hand(seat))?;
+        state.discard_live = false;
+        // No penalty once the deck and discard pile have run dry
+        if seat != state.current_player {
+            if let Some(penalty) = draw_from_deck(state)? {
+                state.card_registry.move_card(penalty, ContainerId::Hand(seat))?;
+            }
         }
+    }
+    
+    let nobody_asked = asked.is_empty();
+    state.last_nunu = Some(NunuReport { discarder, card, asked, claimed_by });
+    if !nobody_asked {
+        for (seat, player) in players.iter_mut().enumerate() {
+            let view = generate_view(state, seat);
+            player.notify_game_update(&view);
+        }
+    }
+    
+    Ok(claimed_by == Some(state.current_player))
+}
+
+/// Returns false, drawing nothing, if the deck is exhausted
+fn execute_draw(state: &mut GameState, decision: DrawDecision) -> Result<bool> {
+    let card_id = match decision {
+        DrawDecision::Deck => match draw_from_deck(state)? {
+            Some(card_id) => card_id,
+            None => return Ok(false),
+        },
         DrawDecision::Discard => {
-            let discard_cards = state.card_registry.get_cards_in_container(&ContainerId::discard());
-            *discard_cards.last().ok_or_else(|| anyhow!("Discard pile is empty"))?
+            if !state.discard_live {
+                return Err(anyhow!("The top discard is dead and can't be drawn"));
+            }
+            state.card_registry.top_card(&ContainerId::Discard)
+                .ok_or_else(|| anyhow!("Discard pile is empty"))?
         }
     };
     
-    // Move card to current player's hand
-    state.card_registry.move_card(card_id, ContainerId::player_hand(state.current_player))?;
+    // Move card to current player's hand; whatever is on the discard pile is now dead
+    state.card_registry.move_card(card_id, ContainerId::Hand(state.current_player))?;
+    state.discard_live = false;
     
-    // TODO: Handle nunu - simplified for now
+    Ok(true)
+}
+
+/// Top card of the deck. An empty deck is first rebuilt from the discard pile, leaving its
+/// top card behind; None if that leaves nothing to draw.
+fn draw_from_deck(state: &mut GameState) -> Result<Option<CardId>> {
+    if state.card_registry.top_card(&ContainerId::Deck).is_none() {
+        reshuffle_discards(state)?;
+    }
+    Ok(state.card_registry.top_card(&ContainerId::Deck))
+}
+
+fn reshuffle_discards(state: &mut GameState) -> Result<()> {
+    let discards = state.card_registry.get_cards_in_container(&ContainerId::Discard);
+    let Some((_top, rest)) = discards.split_last() else {
+        return Ok(());
+    };
+    let mut cards = rest.to_vec();
+    cards.shuffle(&mut thread_rng());
     
+    for card_id in cards {
+        state.card_registry.move_card(card_id, ContainerId::Deck)?;
+    }
     Ok(())
 }
 
-fn validate_move_ledger(state: &GameState, moves: &[CardMove]) -> Result<()> {
+fn validate_move_ledger(state: &GameState, moves: &[CardMove], discard: CardId) -> Result<()> {
     let current_player = state.current_player;
     let has_laid_down = state.players_laid_down[current_player];
     
@@ -140,12 +315,28 @@ fn validate_move_ledger(state: &GameState, moves: &[CardMove]) -> Result<()> {
         validate_single_move(state, card_move, current_player, has_laid_down)?;
     }
     
-    // 2. Validate temp container is empty at end of turn
-    validate_temp_container_end_state(state, moves)?;
+    // 2. The discard comes from the same places a move may take cards from
+    validate_discard_source(state, discard, current_player, has_laid_down)?;
+    
+    // 3. Validate the contract's go-out rule, if it has one
+    validate_one_turn_out(state, moves, discard)?;
     
     Ok(())
 }
 
+fn validate_discard_source(
+    state: &GameState,
+    discard: CardId,
+    current_player: usize,
+    has_laid_down: bool
+) -> Result<()> {
+    validate_move_source(state, discard, current_player, has_laid_down).map_err(|_| {
+        let location = state.card_registry.get_location(discard)
+            .map_or_else(|| "nowhere".to_string(), ContainerId::to_string);
+        anyhow!("Invalid discard: Player {} cannot discard {} from {}", current_player, discard, location)
+    })
+}
+
 fn validate_single_move(
     state: &GameState, 
     card_move: &CardMove, 
@@ -168,27 +359,26 @@ fn validate_move_source(
     has_laid_down: bool
 ) -> Result<()> {
     let current_location = state.card_registry.get_location(card_id)
-        .ok_or_else(|| anyhow!("Card {:?} not found in registry", card_id))?;
+        .ok_or_else(|| anyhow!("Card {} not found in registry", card_id))?;
     
     // Valid sources: player's hand + (if laid down) player's own table sets
-    let player_hand = ContainerId::player_hand(current_player);
-    
-    if current_location == &player_hand {
-        return Ok(()); // Always valid to move from own hand
-    }
-    
-    if has_laid_down {
-        // After laying down, can move from own table sets
-        if is_own_table_container(current_location, current_player) {
-            return Ok(());
+    let valid = match *current_location {
+        ContainerId::Hand(seat) => seat == current_player,
+        ContainerId::Group(seat, _) | ContainerId::Run(seat, _) => {
+            has_laid_down && seat == current_player
         }
-    }
+        ContainerId::Deck | ContainerId::Discard | ContainerId::TableTemp => false,
+    };
     
-    Err(anyhow!(
-        "Invalid source: Player {} cannot move card from container {:?}",
-        current_player,
-        current_location
-    ))
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid source: Player {} cannot move card from container {}",
+            current_player,
+            current_location
+        ))
+    }
 }
 
 fn validate_move_target(
@@ -197,47 +387,49 @@ fn validate_move_target(
     current_player: usize,
     has_laid_down: bool
 ) -> Result<()> {
-    // Always invalid: hands, deck, discard
-    if is_invalid_target_always(target) {
-        return Err(anyhow!("Invalid target: {:?} is never a valid move target", target));
-    }
-    
-    // Always valid: temp container
-    if target == &ContainerId::table_temp() {
-        return Ok(());
-    }
-    
-    // Before laying down: only own empty group/run containers
-    if !has_laid_down {
-        if is_own_empty_set_container(state, target, current_player)? {
-            return Ok(());
-        } else {
-            return Err(anyhow!(
-                "Before laying down, player {} can only move to own empty set containers",
-                current_player
-            ));
+    match *target {
+        // Always invalid: hands, deck, discard
+        ContainerId::Deck | ContainerId::Discard | ContainerId::Hand(_) => {
+            Err(anyhow!("Invalid target: {} is never a valid move target", target))
+        }
+        
+        // Always valid: temp container
+        ContainerId::TableTemp => Ok(()),
+        
+        // Only the sets the contract stipulates can exist on the table
+        ContainerId::Group(_, idx) if idx >= state.contract.required_groups() => Err(anyhow!(
+            "Invalid target: contract {} allows {} group(s) per player, not {}",
+            state.contract,
+            state.contract.required_groups(),
+            target
+        )),
+        ContainerId::Run(_, idx) if idx >= state.contract.required_runs() => Err(anyhow!(
+            "Invalid target: contract {} allows {} run(s) per player, not {}",
+            state.contract,
+            state.contract.required_runs(),
+            target
+        )),
+        
+        // After laying down: any table sets
+        ContainerId::Group(..) | ContainerId::Run(..) if has_laid_down => Ok(()),
+        
+        // Before laying down: only own empty group/run containers
+        ContainerId::Group(seat, _) | ContainerId::Run(seat, _) => {
+            let is_empty = state.card_registry.get_cards_in_container(target).is_empty();
+            if seat == current_player && is_empty {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "Before laying down, player {} can only move to own empty set containers",
+                    current_player
+                ))
+            }
         }
     }
-    
-    // After laying down: any table sets
-    if is_table_set_container(target) {
-        return Ok(());
-    }
-    
-    Err(anyhow!("Invalid target container: {:?}", target))
 }
 
-fn validate_temp_container_end_state(state: &GameState, moves: &[CardMove]) -> Result<()> {
-    // Calculate how many cards will be in temp after all moves
-    let temp_id = ContainerId::table_temp();
-    let initial_temp_count = state.card_registry.get_cards_in_container(&temp_id).len();
-    
-    let moves_to_temp = moves.iter().filter(|m| m.to_container == temp_id).count();
-    let moves_from_temp = moves.iter().filter(|m| {
-        state.card_registry.get_location(m.card_id) == Some(&temp_id)
-    }).count();
-    
-    let final_temp_count = initial_temp_count + moves_to_temp - moves_from_temp;
+fn validate_temp_container_end_state(staged: &CardRegistry) -> Result<()> {
+    let final_temp_count = staged.get_cards_in_container(&ContainerId::TableTemp).len();
     
     if final_temp_count == 0 {
         Ok(())
@@ -249,61 +441,135 @@ fn validate_temp_container_end_state(state: &GameState, moves: &[CardMove]) -> R
     }
 }
 
-// Helper functions
-fn is_invalid_target_always(target: &ContainerId) -> bool {
-    matches!(target, 
-        ContainerId(s) if s.contains("_hand") || 
-                         s == "deck" || 
-                         s == "discard"
-    )
+/// On a one-turn-out contract, laying down must leave nothing in hand but the discard, and
+/// there is no table play after laying down. Leaving the discard out of the count relies on
+/// [`validate_discard_source`] having run first: before laying down, the hand is the only
+/// place a discard can come from.
+fn validate_one_turn_out(state: &GameState, moves: &[CardMove], discard: CardId) -> Result<()> {
+    if !state.contract.one_turn_out() {
+        return Ok(());
+    }
+    let current_player = state.current_player;
+    
+    if state.players_laid_down[current_player] && !moves.is_empty() {
+        return Err(anyhow!(
+            "Contract {} allows no table play after laying down",
+            state.contract
+        ));
+    }
+    
+    let lays_down = moves.iter().any(|m| match m.to_container {
+        ContainerId::Group(seat, _) | ContainerId::Run(seat, _) => seat == current_player,
+        _ => false,
+    });
+    if !lays_down {
+        return Ok(());
+    }
+    
+    let hand_id = ContainerId::Hand(current_player);
+    let left_in_hand = state.card_registry.get_cards_in_container(&hand_id).iter()
+        .filter(|&&card_id| card_id != discard && !moves.iter().any(|m| m.card_id == card_id))
+        .count();
+    if left_in_hand == 0 {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Contract {} must be laid down going out, but {} card(s) would stay in hand",
+            state.contract,
+            left_in_hand
+        ))
+    }
 }
 
-fn is_own_table_container(container: &ContainerId, player: usize) -> bool {
-    let player_prefix = format!("player_{}_", player);
-    container.0.starts_with(&player_prefix) && 
-    (container.0.contains("_group_") || container.0.contains("_run_"))
+/// Applies the move ledger in sequence, then the discard, to a copy of the registry
+fn stage_turn(registry: &CardRegistry, turn_result: &TurnResult) -> Result<CardRegistry> {
+    let mut staged = registry.clone();
+    for card_move in &turn_result.move_ledger {
+        staged.move_card(card_move.card_id, card_move.to_container)?;
+    }
+    staged.move_card(turn_result.discard, ContainerId::Discard)?;
+    Ok(staged)
 }
 
-fn is_own_empty_set_container(
-    state: &GameState, 
-    target: &ContainerId, 
-    player: usize
-) -> Result<bool> {
-    if !is_own_table_container(target, player) {
+/// Before laying down, any cards the player leaves in their own sets must be a full bid for
+/// the contract, made from their hand. Returns true if this turn lays down.
+fn validate_lay_down(state: &GameState, staged: &CardRegistry) -> Result<bool> {
+    let current_player = state.current_player;
+    if state.players_laid_down[current_player] {
         return Ok(false);
     }
     
-    let cards_in_target = state.card_registry.get_cards_in_container(target);
-    Ok(cards_in_target.is_empty())
+    let own_sets = |container: fn(usize, usize) -> ContainerId, count: usize| -> Vec<CardContainer> {
+        (0..count)
+            .map(|idx| staged.get_container_view(&container(current_player, idx)))
+            .filter(|set| !set.cards.is_empty())
+            .collect()
+    };
+    let bid = ContractBid::new(
+        own_sets(ContainerId::Group, state.contract.required_groups()),
+        own_sets(ContainerId::Run, state.contract.required_runs()),
+    );
+    if bid.groups.is_empty() && bid.runs.is_empty() {
+        return Ok(false);
+    }
+    
+    let hand = state.card_registry.get_container_view(&ContainerId::Hand(current_player));
+    bid.validate(&state.contract, &hand).map_err(|reason| {
+        anyhow!("Player {} put cards on the table without laying down the contract: {}", current_player, reason)
+    })?;
+    Ok(true)
 }
 
-fn is_table_set_container(target: &ContainerId) -> bool {
-    target.0.contains("_group_") || target.0.contains("_run_")
+fn is_hand_over(state: &GameState) -> bool {
+    // Check if current player has no cards left
+    let hand_cards = state.card_registry.get_cards_in_container(&ContainerId::Hand(state.current_player));
+    hand_cards.is_empty()
 }
 
-fn execute_move_ledger(state: &mut GameState, moves: Vec<CardMove>) -> Result<()> {
-    // Execute all moves in sequence (validation already passed)
-    for card_move in moves {
-        state.card_registry.move_card(card_move.card_id, card_move.to_container)?;
-    }
-    Ok(())
+/// Every table container the contract allows, for every player, with the set it holds
+fn table_containers(state: &GameState) -> impl Iterator<Item = (ContainerId, SetType)> + '_ {
+    (0..state.num_players).flat_map(move |seat| {
+        let groups = (0..state.contract.required_groups())
+            .map(move |idx| (ContainerId::Group(seat, idx), SetType::Group));
+        let runs = (0..state.contract.required_runs())
+            .map(move |idx| (ContainerId::Run(seat, idx), SetType::Run));
+        groups.chain(runs)
+    })
 }
 
-fn execute_discard(state: &mut GameState, card_id: CardId) -> Result<()> {
-    state.card_registry.move_card(card_id, ContainerId::discard())?;
+/// After the turn, every bid on the table must be valid. Bids can only be created by the
+/// player laying down this turn, in their own containers, and can never be destroyed.
+/// Cards on the table stay there (or go to the discard pile).
+fn validate_table_end_state(state: &GameState, staged: &CardRegistry, lays_down: bool) -> Result<()> {
+    for (container_id, set_type) in table_containers(state) {
+        let before = state.card_registry.get_cards_in_container(&container_id);
+        for &card_id in before {
+            if let Some(ContainerId::Hand(seat)) = staged.get_location(card_id) {
+                return Err(anyhow!("Card {} can't go from {} into player {}'s hand", card_id, container_id, seat));
+            }
+        }
+        
+        let after = staged.get_container_view(&container_id);
+        if after.cards.is_empty() {
+            if !before.is_empty() {
+                return Err(anyhow!("Bid {} can't be destroyed", container_id));
+            }
+            continue;
+        }
+        if before.is_empty() && !(lays_down && container_id.owner() == Some(state.current_player)) {
+            return Err(anyhow!("Bid {} can only be created by laying down", container_id));
+        }
+        
+        set_type.check(&after, state.contract.min_set_size(&set_type))
+            .map_err(|reason| anyhow!("Bid {} {} at end of turn", container_id, reason))?;
+    }
     Ok(())
 }
 
-fn is_hand_over(state: &GameState) -> bool {
-    // Check if current player has no cards left
-    let hand_cards = state.card_registry.get_cards_in_container(&ContainerId::player_hand(state.current_player));
-    hand_cards.is_empty()
-}
-
 /// Intelligently generates a PlayerView by interrogating game state
 fn generate_view(state: &GameState, player_index: usize) -> PlayerView {
-    // Step 1: Determine how many players are actually in the game
-    let num_players = discover_player_count(state);
+    // Step 1: Determine how many players are in the game
+    let num_players = state.num_players;
     
     // Step 2: Calculate relative current player position
     let relative_current_player = calculate_relative_position(
@@ -314,42 +580,42 @@ fn generate_view(state: &GameState, player_index: usize) -> PlayerView {
     
     // Step 3: Get this player's hand
     let held_cards = state.card_registry.get_container_view(
-        &ContainerId::player_hand(player_index)
+        &ContainerId::Hand(player_index)
     );
     
     // Step 4: Discover all active table sets by scanning registry
     let (table_groups, table_runs) = discover_table_sets(state, num_players);
     
-    // Step 5: Get the current discard (if any)
+    // Step 5: Get the current discard (if any, and still live)
     let last_discard = get_current_discard(state);
     
+    // Step 6: Who has laid down, seats relative to this player
+    let players_laid_down = (0..num_players)
+        .map(|offset| state.players_laid_down[(player_index + offset) % num_players])
+        .collect();
+    
+    // Step 7: Report the latest nunu poll, seats relative to this player
+    let relative = |seat| calculate_relative_position(seat, player_index, num_players);
+    let last_nunu = state.last_nunu.as_ref().map(|report| NunuReport {
+        discarder: relative(report.discarder),
+        card: report.card.clone(),
+        asked: report.asked.iter().map(|&seat| relative(seat)).collect(),
+        claimed_by: report.claimed_by.map(relative),
+    });
+    
     PlayerView {
         current_player: relative_current_player,
+        seat: player_index,
         contract: state.contract.clone(),
         held_cards,
         table_groups,
         table_runs,
         last_discard,
+        last_nunu,
+        players_laid_down,
     }
 }
 
-/// Discover how many players are actually in this game by checking for non-empty hands
-fn discover_player_count(state: &GameState) -> usize {
-    let mut max_player = 0;
-    
-    // Check up to 4 possible players
-    for player_id in 0..4 {
-        let hand_cards = state.card_registry.get_cards_in_container(
-            &ContainerId::player_hand(player_id)
-        );
-        if !hand_cards.is_empty() {
-            max_player = player_id;
-        }
-    }
-    
-    max_player + 1 // Convert from 0-based to count
-}
-
 /// Calculate relative position of current player from this player's perspective
 fn calculate_relative_position(current: usize, viewing_player: usize, num_players: usize) -> usize {
     if current >= viewing_player {
@@ -362,29 +628,29 @@ fn calculate_relative_position(current: usize, viewing_player: usize, num_player
 }
 
 /// Discover all active groups and runs on the table by scanning the registry
-fn discover_table_sets(state: &GameState, num_players: usize) -> (Vec<CardContainer>, Vec<CardContainer>) {
+fn discover_table_sets(state: &GameState, num_players: usize) -> (Vec<TableSet>, Vec<TableSet>) {
     let mut table_groups = Vec::new();
     let mut table_runs = Vec::new();
     
     // Scan all possible containers for each player
     for player_id in 0..num_players {
-        // Check for groups (up to reasonable limit)
-        for group_idx in 0..5 { // Most contracts have max 3 groups, so 5 is safe
-            let container_id = ContainerId::player_group(player_id, group_idx);
+        // Check for groups (only the contract's groups can exist)
+        for group_idx in 0..state.contract.required_groups() {
+            let container_id = ContainerId::Group(player_id, group_idx);
             let container_view = state.card_registry.get_container_view(&container_id);
             
             if !container_view.cards.is_empty() {
-                table_groups.push(container_view);
+                table_groups.push(TableSet { id: container_id, cards: container_view });
             }
         }
         
-        // Check for runs (up to reasonable limit)
-        for run_idx in 0..5 { // Most contracts have max 3 runs, so 5 is safe
-            let container_id = ContainerId::player_run(player_id, run_idx);
+        // Check for runs (only the contract's runs can exist)
+        for run_idx in 0..state.contract.required_runs() {
+            let container_id = ContainerId::Run(player_id, run_idx);
             let container_view = state.card_registry.get_container_view(&container_id);
             
             if !container_view.cards.is_empty() {
-                table_runs.push(container_view);
+                table_runs.push(TableSet { id: container_id, cards: container_view });
             }
         }
     }
@@ -392,21 +658,296 @@ fn discover_table_sets(state: &GameState, num_players: usize) -> (Vec<CardContai
     (table_groups, table_runs)
 }
 
-/// Get the current top discard, handling edge cases gracefully
-fn get_current_discard(state: &GameState) -> CardView {
-    let discard_cards = state.card_registry.get_cards_in_container(&ContainerId::discard());
+/// Get the current top discard; None once it's dead
+fn get_current_discard(state: &GameState) -> Option<CardView> {
+    if !state.discard_live {
+        return None;
+    }
+    state.card_registry.top_card(&ContainerId::Discard).map(card_id_to_view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Plays whatever turn it is given, and asks for every discard it's offered if `nunu`
+    #[derive(Debug, Default)]
+    struct Scripted {
+        nunu: bool,
+        turn: Option<TurnResult>,
+    }
     
-    if let Some(&top_discard_id) = discard_cards.last() {
-        card_id_to_view(top_discard_id)
-    } else {
-        // No discards yet - this can happen at start of game
-        // Return a dummy card that's clearly invalid
-        CardView {
-            id: CardId::new(255, 255), // Invalid ID
-            rank: Rank::Ace,
-            suit: Suit::None,
-            score_value: 0,
+    impl Player for Scripted {
+        fn check_nunu(&mut self, _view: &PlayerView, _discarded_card: &CardView) -> bool {
+            self.nunu
         }
+        
+        fn draw_decision(&mut self, _view: &PlayerView) -> DrawDecision {
+            DrawDecision::Deck
+        }
+        
+        fn play_turn(&mut self, _view: &PlayerView) -> Result<TurnResult> {
+            self.turn.take().ok_or_else(|| anyhow!("No turn scripted"))
+        }
+        
+        fn notify_game_update(&mut self, _view: &PlayerView) {}
+    }
+    
+    fn cards(s: &str) -> Vec<CardId> {
+        let container: CardContainer = s.parse().unwrap();
+        container.cards.iter().map(|card| card.id).collect()
+    }
+    
+    /// Seat 0 to play, with the given hands; every other card is in the deck
+    fn state(contract: &str, hands: &[&str]) -> GameState {
+        let mut card_registry = CardRegistry::new();
+        card_registry.initialize_with_deck(&DeckConfig::for_players(hands.len())).unwrap();
+        for (seat, hand) in hands.iter().enumerate() {
+            for card in cards(hand) {
+                card_registry.move_card(card, ContainerId::Hand(seat)).unwrap();
+            }
+        }
+        GameState {
+            card_registry,
+            num_players: hands.len(),
+            current_player: 0,
+            contract: contract.parse().unwrap(),
+            players_laid_down: vec![false; hands.len()],
+            discard_live: false,
+            last_discarder: None,
+            last_nunu: None,
+        }
+    }
+    
+    fn turn(moves: &[(&str, ContainerId)], discard: &str) -> TurnResult {
+        let move_ledger = moves.iter()
+            .flat_map(|&(s, to_container)| {
+                cards(s).into_iter().map(move |card_id| CardMove { card_id, to_container })
+            })
+            .collect();
+        TurnResult { move_ledger, discard: cards(discard)[0] }
+    }
+    
+    /// Runs the current seat's turn with the given play; nobody asks for the discard
+    fn play(state: &mut GameState, turn: TurnResult) -> Result<TurnOutcome> {
+        play_asking(state, turn, &[])
+    }
+    
+    /// Like `play`, but the seats in `asking` ask for the discard
+    fn play_asking(state: &mut GameState, turn: TurnResult, asking: &[usize]) -> Result<TurnOutcome> {
+        let mut players: Vec<Box<dyn Player>> = (0..state.num_players)
+            .map(|seat| Box::new(Scripted { nunu: asking.contains(&seat), turn: None }) as Box<dyn Player>)
+            .collect();
+        players[state.current_player] = Box::new(Scripted {
+            nunu: asking.contains(&state.current_player),
+            turn: Some(turn),
+        });
+        run_turn(&mut players, state)
+    }
+    
+    fn hand(state: &GameState, seat: usize) -> Vec<CardId> {
+        state.card_registry.get_cards_in_container(&ContainerId::Hand(seat)).to_vec()
+    }
+    
+    /// Puts sets on the table as if `seat` had laid them down on an earlier turn
+    fn laid_down(state: &mut GameState, seat: usize, sets: &[(&str, ContainerId)]) {
+        for &(set, container) in sets {
+            for card in cards(set) {
+                state.card_registry.move_card(card, container).unwrap();
+            }
+        }
+        state.players_laid_down[seat] = true;
+    }
+    
+    #[test]
+    fn laying_down_a_full_bid_is_recorded() {
+        let mut state = state("GR:10", &["9C 9D 9S 5H 6H 7H 8H KC QD 3S", "AS"]);
+        let lay_down = turn(
+            &[("9C 9D 9S", ContainerId::Group(0, 0)), ("5H 6H 7H 8H", ContainerId::Run(0, 0))],
+            "KC",
+        );
+        
+        assert!(matches!(play(&mut state, lay_down).unwrap(), TurnOutcome::Continue));
+        assert_eq!(state.players_laid_down, vec![true, false]);
+        assert_eq!(state.card_registry.top_card(&ContainerId::Discard), Some(cards("KC")[0]));
+        assert_eq!(hand(&state, 0).len(), 3); // QD 3S and the card drawn
+        assert_eq!(state.current_player, 1);
+    }
+    
+    #[test]
+    fn partial_lay_down_is_rejected() {
+        let mut state = state("GR:10", &["9C 9D 9S 5H 6H 7H 8H KC QD 3S", "AS"]);
+        let group_only = turn(&[("9C 9D 9S", ContainerId::Group(0, 0))], "KC");
+        
+        let error = play(&mut state, group_only).unwrap_err().to_string();
+        assert!(error.contains("without laying down"), "{}", error);
+        assert_eq!(state.players_laid_down, vec![false, false]);
+        assert!(state.card_registry.get_cards_in_container(&ContainerId::Group(0, 0)).is_empty());
+    }
+    
+    #[test]
+    fn discard_must_come_from_the_players_hand() {
+        for discard in ["AS", "KH"] {
+            // The Ace is in seat 1's hand; the King is still in the deck
+            let mut state = state("GR:10", &["9C 9D 9S 5H 6H 7H 8H KC QD 3S", "AS"]);
+            let error = play(&mut state, turn(&[], discard)).unwrap_err().to_string();
+            assert!(error.contains("Invalid discard"), "{}", error);
+            assert_eq!(hand(&state, 1), cards("AS"));
+        }
+        
+        // Laying down every card can't stand in for the discard
+        let mut state = state("GR:7", &["9C 9D 9S 5H 6H 7H 8H", "AS"]);
+        let everything = turn(
+            &[("9C 9D 9S", ContainerId::Group(0, 0)), ("5H 6H 7H 8H", ContainerId::Run(0, 0))],
+            "AS",
+        );
+        assert!(play(&mut state, everything).is_err());
+    }
+    
+    #[test]
+    fn only_the_contracts_sets_can_be_used() {
+        let hand = "9C 9D 9S 5H 6H 7H 8H 10C JC QC KC AD";
+        let group = turn(&[("9C 9D 9S", ContainerId::Group(0, 0))], "AD");
+        let third_run = turn(&[("5H 6H 7H 8H", ContainerId::Run(0, 2))], "AD");
+        for (bad_turn, expected) in [(group, "0 group(s)"), (third_run, "2 run(s)")] {
+            let mut state = state("RR:12", &[hand, "AS"]);
+            let error = play(&mut state, bad_turn).unwrap_err().to_string();
+            assert!(error.contains(expected), "{}", error);
+        }
+        
+        let mut state = state("RR:12", &[hand, "AS"]);
+        let runs = turn(
+            &[("5H 6H 7H 8H", ContainerId::Run(0, 0)), ("10C JC QC KC", ContainerId::Run(0, 1))],
+            "AD",
+        );
+        assert!(play(&mut state, runs).is_ok());
+        assert!(state.players_laid_down[0]);
+    }
+    
+    /// Seat 0 has laid down 9s and Kings and holds 9H 5C 5D 5S AD; seat 1 holds AS
+    fn table(seat_1_laid_down: bool) -> GameState {
+        let mut state = state("GG:10", &["9H 5C 5D 5S AD", "AS"]);
+        let sets = [("9C 9D 9S", ContainerId::Group(0, 0)), ("KC KD KS", ContainerId::Group(0, 1))];
+        laid_down(&mut state, 0, &sets);
+        if seat_1_laid_down {
+            let sets = [("9C#2 9D#2 9S#2", ContainerId::Group(1, 0)), ("QC QD QS", ContainerId::Group(1, 1))];
+            laid_down(&mut state, 1, &sets);
+        }
+        state
+    }
+    
+    #[test]
+    fn gratuity_onto_a_set_is_accepted() {
+        let mut state = table(false);
+        let gratuity = turn(&[("9H", ContainerId::Group(0, 0))], "AD");
+        assert!(matches!(play(&mut state, gratuity), Ok(TurnOutcome::Continue)));
+        assert_eq!(state.card_registry.get_cards_in_container(&ContainerId::Group(0, 0)).len(), 4);
+        assert_eq!(hand(&state, 0).len(), 4); // 5C 5D 5S and the card drawn
+    }
+    
+    #[test]
+    fn table_rules_are_enforced() {
+        let cases = [
+            // Moving a whole set onto another seat's
+            (true, turn(&[("9C 9D 9S", ContainerId::Group(1, 0))], "AD"), "can't be destroyed"),
+            (false, turn(&[("KC", ContainerId::Hand(0))], "AD"), "never a valid move target"),
+            (false, turn(&[("KC KD", ContainerId::Group(0, 0))], "AD"), "at end of turn"),
+            (false, turn(&[("5C 5D 5S", ContainerId::Group(1, 0))], "AD"), "only be created by laying down"),
+        ];
+        for (seat_1_laid_down, bad_turn, expected) in cases {
+            let mut state = table(seat_1_laid_down);
+            let error = play(&mut state, bad_turn).unwrap_err().to_string();
+            assert!(error.contains(expected), "{}", error);
+            let nines = state.card_registry.get_cards_in_container(&ContainerId::Group(0, 0));
+            assert_eq!(nines, &cards("9C 9D 9S")[..]);
+            assert_eq!(hand(&state, 0).len(), 6); // The card drawn is kept
+        }
+    }
+    
+    /// Seat 1 has just discarded 7H and seat 2 is to play
+    fn after_discard() -> GameState {
+        let mut state = state("GR:10", &["AS", "AD", "KC QD 3S", "AC"]);
+        state.card_registry.move_card(cards("7H")[0], ContainerId::Discard).unwrap();
+        state.current_player = 2;
+        state.discard_live = true;
+        state.last_discarder = Some(1);
+        state
+    }
+    
+    #[test]
+    fn nunu_goes_to_the_first_to_ask_with_a_penalty() {
+        let mut state = after_discard();
+        play_asking(&mut state, turn(&[], "KC"), &[0, 3]).unwrap();
+        
+        let report = state.last_nunu.clone().unwrap();
+        assert_eq!(report.asked, vec![3, 0]);
+        assert_eq!(report.claimed_by, Some(3));
+        assert!(hand(&state, 3).contains(&cards("7H")[0]));
+        assert_eq!(hand(&state, 3).len(), 3); // AC, 7H and the penalty card
+        assert_eq!(hand(&state, 0), cards("AS"));
+        assert_eq!(hand(&state, 2).len(), 3); // Seat 2 still drew from the deck
+    }
+    
+    #[test]
+    fn nunu_by_the_active_player_is_their_draw() {
+        let mut state = after_discard();
+        let deck_size = state.card_registry.get_cards_in_container(&ContainerId::Deck).len();
+        play_asking(&mut state, turn(&[], "KC"), &[0, 2]).unwrap();
+        
+        assert_eq!(state.last_nunu.clone().unwrap().claimed_by, Some(2));
+        assert_eq!(hand(&state, 2), cards("QD 3S 7H"));
+        assert_eq!(hand(&state, 0), cards("AS"));
+        assert_eq!(state.card_registry.get_cards_in_container(&ContainerId::Deck).len(), deck_size);
+    }
+    
+    /// Moves every card left in the deck to `container`, returning them bottom to top
+    fn empty_deck(state: &mut GameState, container: ContainerId) -> Vec<CardId> {
+        let deck = state.card_registry.get_cards_in_container(&ContainerId::Deck).to_vec();
+        for &card in &deck {
+            state.card_registry.move_card(card, container).unwrap();
+        }
+        deck
+    }
+    
+    #[test]
+    fn empty_deck_is_refilled_from_the_discards() {
+        let mut state = state("GR:10", &["KC QD", "AS"]);
+        let discards = empty_deck(&mut state, ContainerId::Discard);
+        let top = *discards.last().unwrap();
+        
+        assert!(matches!(play(&mut state, turn(&[], "KC")), Ok(TurnOutcome::Continue)));
+        let discard_pile = state.card_registry.get_cards_in_container(&ContainerId::Discard);
+        assert_eq!(discard_pile, &[top, cards("KC")[0]]);
+        // All but the top discard, less the card drawn
+        let deck = state.card_registry.get_cards_in_container(&ContainerId::Deck);
+        assert_eq!(deck.len(), discards.len() - 2);
+        let drawn = hand(&state, 0)[1];
+        assert!(drawn != top && discards.contains(&drawn));
+    }
+    
+    #[test]
+    fn nothing_to_draw_is_a_stalemate() {
+        let mut state = state("GR:10", &["KC QD", "AS"]);
+        let mut dealt = empty_deck(&mut state, ContainerId::Hand(1));
+        state.card_registry.move_card(dealt.pop().unwrap(), ContainerId::Discard).unwrap();
+        
+        assert!(matches!(play(&mut state, turn(&[], "KC")), Ok(TurnOutcome::Stalemate)));
+        assert_eq!(hand(&state, 0), cards("KC QD"));
+        assert_eq!(state.current_player, 0);
+    }
+    
+    #[test]
+    fn rearrangements_pass_validation() {
+        let mut state = state("GR:10", &["8H AD", "AS"]);
+        let sets = [("7C 7D 7S 7H", ContainerId::Group(0, 0)), ("3H 4H 5H 6H", ContainerId::Run(0, 0))];
+        laid_down(&mut state, 0, &sets);
+        let view = generate_view(&state, 0);
+        let new: CardContainer = "8H".parse().unwrap();
+        let table = view.table_groups.iter().chain(&view.table_runs);
+        let found = crate::rearrange::find_rearrangement(table, &new.cards, &state.contract, 0, 2).unwrap();
+        
+        let rearranged = TurnResult { move_ledger: found.moves, discard: cards("AD")[0] };
+        assert!(matches!(play(&mut state, rearranged), Ok(TurnOutcome::Continue)));
+        assert_eq!(state.card_registry.get_cards_in_container(&ContainerId::Run(0, 0)).len(), 6);
     }
 }
-
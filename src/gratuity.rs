@@ -0,0 +1,94 @@
+use crate::card::{CardContainer, CardId, CardView, ContainerId, Rank, SetType};
+use crate::player::{CardMove, TableSet};
+
+// Gratuity: single cards played from the hand onto sets already on the table (WHAT.md).
+// A play is legal when the set plus the card is still a valid set of the same type.
+
+/// How a card joins a table set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GratuityKind {
+    JoinGroup,
+    ExtendRunLow,     // Below the run's lowest position
+    ExtendRunHigh,    // Above the run's highest position
+    FillWildPosition, // A natural taking the place of a wild, which moves to an end
+}
+
+/// A card from the hand that can be played onto a table set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GratuityPlay {
+    pub card: CardId,
+    pub target: ContainerId,
+    pub kind: GratuityKind,
+}
+
+impl GratuityPlay {
+    /// The ledger entry that makes this play
+    pub fn to_move(&self) -> CardMove {
+        CardMove { card_id: self.card, to_container: self.target }
+    }
+}
+
+/// Every legal single-card play from `hand` onto the table sets. A wild that could extend a
+/// run at either end is listed once for each end.
+pub fn find_gratuities<'a>(
+    table: impl IntoIterator<Item = &'a TableSet>,
+    hand: &CardContainer,
+) -> Vec<GratuityPlay> {
+    let mut plays = Vec::new();
+    for set in table {
+        let Some(set_type) = set.id.set_type() else {
+            continue;
+        };
+        for card in &hand.cards {
+            let mut extended = set.cards.clone();
+            extended.cards.push(card.clone());
+            if set_type.resolve(&extended).is_empty() {
+                continue;
+            }
+            
+            let kinds = match set_type {
+                SetType::Group => vec![GratuityKind::JoinGroup],
+                SetType::Run => run_play_kinds(&set.cards, &extended, card),
+            };
+            plays.extend(kinds.into_iter().map(|kind| GratuityPlay { card: card.id, target: set.id, kind }));
+        }
+    }
+    plays
+}
+
+/// How a card that validly extends `run` (giving `extended`) joins it
+fn run_play_kinds(run: &CardContainer, extended: &CardContainer, card: &CardView) -> Vec<GratuityKind> {
+    let spans: Vec<(u8, u8)> = SetType::Run.resolve(run).iter().map(|r| r.span).collect();
+    
+    if card.rank.is_wild() {
+        // A wild goes on an end: whichever ends the longer run can sit at
+        let new_spans: Vec<(u8, u8)> = SetType::Run.resolve(extended).iter().map(|r| r.span).collect();
+        let mut kinds = Vec::new();
+        if spans.iter().any(|&(low, high)| new_spans.contains(&(low - 1, high))) {
+            kinds.push(GratuityKind::ExtendRunLow);
+        }
+        if spans.iter().any(|&(low, high)| new_spans.contains(&(low, high + 1))) {
+            kinds.push(GratuityKind::ExtendRunHigh);
+        }
+        return kinds;
+    }
+    
+    let positions: &[u8] = match card.rank {
+        Rank::Ace => &[1, 14],
+        _ => &[card.rank as u8],
+    };
+    // Where the wilds can be read more than one way, filling a wild's place wins over extending
+    let fits = |at: &dyn Fn(u8, u8, u8) -> bool| {
+        positions.iter().any(|&pos| spans.iter().any(|&(low, high)| at(pos, low, high)))
+    };
+    let kind = if fits(&|pos, low, high| (low..=high).contains(&pos)) {
+        Some(GratuityKind::FillWildPosition)
+    } else if fits(&|pos, low, _| pos + 1 == low) {
+        Some(GratuityKind::ExtendRunLow)
+    } else if fits(&|pos, _, high| pos == high + 1) {
+        Some(GratuityKind::ExtendRunHigh)
+    } else {
+        None
+    };
+    kind.into_iter().collect()
+}
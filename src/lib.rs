@@ -1,19 +1,31 @@
 pub mod card;
+pub mod notation;
+pub mod multiset;
 pub mod contract; 
+pub mod solver;
+pub mod probability;
 pub mod player;
+pub mod gratuity;
+pub mod rearrange;
+pub mod scoring;
 pub mod engine;
 pub mod test_player;
 
 // Re-export commonly used types
-pub use card::{CardId, CardView, CardContainer, CardRegistry, ContainerId, SetType};
-pub use contract::{ContractOrder, ContractBid};
-pub use player::{Player, PlayerView, DrawDecision, CardMove, TurnResult};
+pub use card::{CardId, CardView, CardContainer, CardRegistry, ContainerId, DeckConfig, SetType};
+pub use contract::{ContractOrder, ContractBid, ContractSchedule, BidError};
+pub use scoring::{ScoringTable, HandOutcome, Scoresheet};
+pub use player::{Player, PlayerView, DrawDecision, CardMove, TurnResult, TableSet, NunuReport};
 
 // Game state types
 #[derive(Debug)]
 pub struct GameState {
     pub card_registry: CardRegistry, // All card locations - the ground truth
+    pub num_players: usize,
     pub current_player: usize,
     pub contract: ContractOrder,
     pub players_laid_down: Vec<bool>, // Track which players have fulfilled their contract
+    pub discard_live: bool, // Top discard can still be drawn; dead once the next player draws
+    pub last_discarder: Option<usize>, // None for the discard turned up at the deal
+    pub last_nunu: Option<NunuReport>, // Latest poll for the discard, in absolute seats
 }
\ No newline at end of file
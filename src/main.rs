@@ -1,28 +1,41 @@
 use anyhow::Result;
-use rumminator::engine::hand;
+use rumminator::ContractSchedule;
+use rumminator::engine::{game, HouseRules};
 use rumminator::test_player::BasicPlayer;
 
 fn main() -> Result<()> {
     println!("🃏 Starting Rumminator Game Engine");
     
     // Create basic players (testing with fewer for now)
-    let players: Vec<Box<dyn rumminator::Player>> = vec![
+    let mut players: Vec<Box<dyn rumminator::Player>> = vec![
         Box::new(BasicPlayer::new()),
         Box::new(BasicPlayer::new()),
     ];
     
     println!("Players: 2 BasicPlayers");
     
-    // Run a single hand
-    println!("🎮 Starting hand 1...");
-    match hand(players, 1) {
-        Ok(()) => {
-            println!("🏁 Hand test completed!");
+    // Play every hand of the standard schedule
+    let schedule = ContractSchedule::standard();
+    println!("🎮 Starting a {}-hand game: {}", schedule.len(), schedule);
+    match game(&mut players, &schedule, &HouseRules::default()) {
+        Ok(scoresheet) => {
+            println!("🏁 Game completed!");
+            for (idx, outcome) in scoresheet.hands().iter().enumerate() {
+                match outcome.winner {
+                    Some(seat) => {
+                        println!("  Hand {}: Player {} went out after {} turns", idx + 1, seat, outcome.turns);
+                    }
+                    None => println!("  Hand {}: stalemate after {} turns", idx + 1, outcome.turns),
+                }
+            }
+            for (place, (seat, total)) in scoresheet.standings().iter().enumerate() {
+                println!("  {}. Player {}: {} points", place + 1, seat, total);
+            }
         }
         Err(e) => {
-            println!("❌ Hand error: {}", e);
+            println!("❌ Game error: {}", e);
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
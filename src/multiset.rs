@@ -0,0 +1,296 @@
+use crate::card::{card_id_to_view, CardContainer, CardId, Rank, SetType, Suit};
+
+// Compact multiset of cards for fast hand evaluation.
+//
+// Cards are counted by base card (1-54), ignoring which deck a copy came from. Counts are stored
+// bit-sliced across two u64 planes: bit (base - 1) of `low` holds the 1s bit of that card's count
+// and the same bit of `high` holds the 2s bit. That covers up to 3 copies of each card, and lets
+// union, difference and counting run as a handful of word operations.
+
+const HEARTS: u64 = (1 << 13) - 1;
+const SUIT_MASKS: [u64; 4] = [HEARTS, HEARTS << 13, HEARTS << 26, HEARTS << 39];
+const JOKERS: u64 = 0b11 << 52;
+const TWOS: u64 = rank_mask(2);
+const WILDS: u64 = TWOS | JOKERS;
+
+/// Bits for every suit's card of the given rank (1 = Ace ... 13 = King)
+const fn rank_mask(rank: u8) -> u64 {
+    let bit = 1u64 << (rank - 1);
+    bit | bit << 13 | bit << 26 | bit << 39
+}
+
+/// Most copies of a single card a multiset can hold
+pub const MAX_COPIES: u8 = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardMultiset {
+    low: u64,
+    high: u64,
+}
+
+impl CardMultiset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Adds one copy of a card (saturating at MAX_COPIES)
+    pub fn insert(&mut self, card: CardId) {
+        let bit = 1u64 << (card.base_card() - 1);
+        *self = self.union(&CardMultiset { low: bit, high: 0 });
+    }
+    
+    /// Removes one copy of a card, returning false if none was present
+    pub fn remove(&mut self, card: CardId) -> bool {
+        let bit = 1u64 << (card.base_card() - 1);
+        if (self.low | self.high) & bit == 0 {
+            return false;
+        }
+        *self = self.difference(&CardMultiset { low: bit, high: 0 });
+        true
+    }
+    
+    /// Copies held of a base card (1-54)
+    pub fn count(&self, base_card: u8) -> u8 {
+        let shift = base_card - 1;
+        ((self.low >> shift) & 1) as u8 + 2 * ((self.high >> shift) & 1) as u8
+    }
+    
+    pub fn len(&self) -> usize {
+        self.low.count_ones() as usize + 2 * self.high.count_ones() as usize
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.low | self.high == 0
+    }
+    
+    /// Multiset sum: counts add, saturating at MAX_COPIES
+    pub fn union(&self, other: &CardMultiset) -> CardMultiset {
+        let low = self.low ^ other.low;
+        let carry = self.low & other.low;
+        let high = self.high ^ other.high ^ carry;
+        let overflow = (self.high & other.high) | (carry & (self.high ^ other.high));
+        CardMultiset { low: low | overflow, high: high | overflow }
+    }
+    
+    /// Multiset difference: counts subtract, stopping at zero
+    pub fn difference(&self, other: &CardMultiset) -> CardMultiset {
+        let low = self.low ^ other.low;
+        let borrow = !self.low & other.low;
+        let high = self.high ^ other.high ^ borrow;
+        let underflow = (!self.high & other.high) | (!(self.high ^ other.high) & borrow);
+        CardMultiset { low: low & !underflow, high: high & !underflow }
+    }
+    
+    /// True if every card of `other` is held at least as many times here
+    pub fn contains(&self, other: &CardMultiset) -> bool {
+        other.difference(self).is_empty()
+    }
+    
+    /// Number of cards of a rank across all suits
+    pub fn count_rank(&self, rank: Rank) -> usize {
+        let mask = match rank {
+            Rank::Joker => JOKERS,
+            rank => rank_mask(rank as u8),
+        };
+        self.count_masked(mask)
+    }
+    
+    /// Number of cards of a suit (Suit::None counts Jokers)
+    pub fn count_suit(&self, suit: Suit) -> usize {
+        self.count_masked(suit_mask(suit))
+    }
+    
+    fn count_masked(&self, mask: u64) -> usize {
+        (self.low & mask).count_ones() as usize + 2 * (self.high & mask).count_ones() as usize
+    }
+    
+    /// Bits of every card held at least once
+    fn present(&self) -> u64 {
+        self.low | self.high
+    }
+    
+    /// Base cards held, with their counts
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (1..=54).map(|base| (base, self.count(base))).filter(|&(_, count)| count > 0)
+    }
+    
+    /// Materializes the multiset as cards, taking copies from deck 1 upwards
+    pub fn to_container(&self) -> CardContainer {
+        let cards = self.iter()
+            .flat_map(|(base, count)| (1..=count).map(move |deck| CardId::new(base, deck)))
+            .map(card_id_to_view)
+            .collect();
+        CardContainer { cards }
+    }
+    
+    /// Same rules as [`CardContainer::is_valid_group`]
+    pub fn is_valid_group(&self) -> bool {
+        if self.len() < SetType::Group.min_size() {
+            return false;
+        }
+        
+        let naturals = self.present() & !WILDS;
+        if naturals == 0 {
+            return self.present() & TWOS != 0; // A group of 2s
+        }
+        let rank = naturals.trailing_zeros() % 13 + 1;
+        naturals & !rank_mask(rank as u8) == 0
+    }
+    
+    /// Same rules as [`CardContainer::is_valid_run`]
+    pub fn is_valid_run(&self) -> bool {
+        let len = self.len();
+        if len < SetType::Run.min_size() || len > 13 {
+            return false;
+        }
+        
+        // Every non-Joker card, 2s included, shares one suit
+        let suited = self.present() & !JOKERS;
+        if suited == 0 {
+            return false;
+        }
+        let suit_idx = suited.trailing_zeros() / 13;
+        let suit = SUIT_MASKS[suit_idx as usize];
+        if suited & !suit != 0 {
+            return false;
+        }
+        
+        // Naturals can't repeat, and must fit in a window of `len` positions
+        let naturals = suited & !TWOS;
+        if self.high & naturals != 0 {
+            return false;
+        }
+        let ranks = (naturals >> (suit_idx * 13)) as u16; // Bit 0 = Ace
+        if ranks == 0 {
+            return true;
+        }
+        let fits = |positions: u32| {
+            let span = 32 - positions.leading_zeros() - positions.trailing_zeros();
+            span as usize <= len
+        };
+        let low_aces = (ranks as u32) << 1; // Bit n = position n, Ace at 1
+        let high_aces = (low_aces & !0b10) | ((ranks as u32 & 1) << 14); // Ace at 14
+        fits(low_aces) || fits(high_aces)
+    }
+    
+    pub fn is_valid(&self, set_type: &SetType) -> bool {
+        match set_type {
+            SetType::Group => self.is_valid_group(),
+            SetType::Run => self.is_valid_run(),
+        }
+    }
+}
+
+fn suit_mask(suit: Suit) -> u64 {
+    match suit {
+        Suit::Hearts => SUIT_MASKS[0],
+        Suit::Diamonds => SUIT_MASKS[1],
+        Suit::Clubs => SUIT_MASKS[2],
+        Suit::Spades => SUIT_MASKS[3],
+        Suit::None => JOKERS,
+    }
+}
+
+impl From<&CardContainer> for CardMultiset {
+    fn from(container: &CardContainer) -> Self {
+        let mut set = CardMultiset::new();
+        for card in &container.cards {
+            set.insert(card.id);
+        }
+        set
+    }
+}
+
+impl From<&CardMultiset> for CardContainer {
+    fn from(set: &CardMultiset) -> Self {
+        set.to_container()
+    }
+}
+
+impl FromIterator<CardId> for CardMultiset {
+    fn from_iter<I: IntoIterator<Item = CardId>>(iter: I) -> Self {
+        let mut set = CardMultiset::new();
+        for card in iter {
+            set.insert(card);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use super::*;
+    use crate::card::DeckConfig;
+    
+    fn with_counts(counts: &[u8; 54]) -> CardMultiset {
+        (1..=54u8)
+            .flat_map(|base| (1..=counts[base as usize - 1]).map(move |deck| CardId::new(base, deck)))
+            .collect()
+    }
+    
+    #[test]
+    fn union_and_difference_match_counting() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..1000 {
+            let a: [u8; 54] = std::array::from_fn(|_| rng.gen_range(0..=MAX_COPIES));
+            let b: [u8; 54] = std::array::from_fn(|_| rng.gen_range(0..=MAX_COPIES));
+            let (set_a, set_b) = (with_counts(&a), with_counts(&b));
+            let (union, difference) = (set_a.union(&set_b), set_a.difference(&set_b));
+            for base in 1..=54u8 {
+                let (x, y) = (a[base as usize - 1], b[base as usize - 1]);
+                assert_eq!(union.count(base), (x + y).min(MAX_COPIES), "{} + {}", x, y);
+                assert_eq!(difference.count(base), x.saturating_sub(y), "{} - {}", x, y);
+            }
+            assert_eq!(set_a.contains(&set_b), a.iter().zip(&b).all(|(x, y)| x >= y));
+        }
+    }
+    
+    #[test]
+    fn insert_saturates_and_remove_stops_at_zero() {
+        let mut set = CardMultiset::new();
+        let ace = CardId::new(1, 1);
+        for _ in 0..5 {
+            set.insert(ace);
+        }
+        assert_eq!(set.count(1), MAX_COPIES);
+        for _ in 0..MAX_COPIES {
+            assert!(set.remove(ace));
+        }
+        assert!(!set.remove(ace));
+        assert!(set.is_empty());
+    }
+    
+    #[test]
+    fn validation_agrees_with_containers() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let shoe: Vec<CardId> = DeckConfig { decks: 3, jokers: true }.cards().collect();
+        for _ in 0..20_000 {
+            // Cards near one rank and one run, plus wilds, so plenty of sets come out valid
+            let rank = rng.gen_range(1..=13u8);
+            let suit = rng.gen_range(0..4u8);
+            let low = rng.gen_range(1..=9u8);
+            let near = |card: &CardId| {
+                let base = card.base_card();
+                let (card_suit, card_rank) = ((base - 1) / 13, (base - 1) % 13 + 1);
+                base > 52 || card_rank == 2 || card_rank == rank
+                    || (card_suit == suit && (low..low + 5).contains(&card_rank))
+            };
+            let pool: Vec<CardId> = shoe.iter().copied().filter(near).collect();
+            let size = rng.gen_range(3..=8);
+            let cards = CardContainer {
+                cards: (0..size).map(|_| card_id_to_view(pool[rng.gen_range(0..pool.len())])).collect(),
+            };
+            let mut seen = HashSet::new();
+            if !cards.cards.iter().all(|card| seen.insert(card.id)) {
+                continue; // The same physical card twice
+            }
+            
+            let set = CardMultiset::from(&cards);
+            assert_eq!(set.is_valid_group(), cards.is_valid_group(), "group {}", cards);
+            assert_eq!(set.is_valid_run(), cards.is_valid_run(), "run {}", cards);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{Result, anyhow};
+use crate::card::{card_id_to_view, CardContainer, CardId, CardView, Rank, Suit, RANKS_BY_VALUE, SUITS_BY_BASE};
+
+// Card notation, as described in IMPL.md:
+// - concise: "QH", "10D", "2C", "J-" (Joker)
+// - unicode: "Q♥", "10♦", "2♣", "J-"
+// - long: "Queen of hearts", "2 of clubs", "Joker"
+// A CardId also names its physical copy: "QH#2" is the Queen of hearts from deck 2. Jokers are
+// numbered across decks (two per deck), so "J-#3" is the first Joker of deck 2.
+
+const JOKER_BASE: u8 = 53;
+const JOKERS_PER_DECK: u8 = 2;
+
+impl Rank {
+    /// Concise designation: "A", "2".."10", "J", "Q", "K" (a Joker is "J" with no suit)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Rank::Ace => "A",
+            Rank::Two => "2", Rank::Three => "3", Rank::Four => "4", Rank::Five => "5",
+            Rank::Six => "6", Rank::Seven => "7", Rank::Eight => "8", Rank::Nine => "9",
+            Rank::Ten => "10",
+            Rank::Jack | Rank::Joker => "J",
+            Rank::Queen => "Q",
+            Rank::King => "K",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Ace => "Ace",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+            Rank::Joker => "Joker",
+            _ => self.symbol(),
+        }
+    }
+}
+
+impl Suit {
+    /// Concise designation: "H", "D", "C", "S", or "-" for Jokers
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::Hearts => 'H',
+            Suit::Diamonds => 'D',
+            Suit::Clubs => 'C',
+            Suit::Spades => 'S',
+            Suit::None => '-',
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Suit::Hearts => '♥',
+            Suit::Diamonds => '♦',
+            Suit::Clubs => '♣',
+            Suit::Spades => '♠',
+            Suit::None => '-',
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Suit::Hearts => "hearts",
+            Suit::Diamonds => "diamonds",
+            Suit::Clubs => "clubs",
+            Suit::Spades => "spades",
+            Suit::None => "none",
+        }
+    }
+
+    fn parse_char(c: char) -> Option<Suit> {
+        match c.to_ascii_uppercase() {
+            'H' | '♥' | '♡' => Some(Suit::Hearts),
+            'D' | '♦' | '♢' => Some(Suit::Diamonds),
+            'C' | '♣' | '♧' => Some(Suit::Clubs),
+            'S' | '♠' | '♤' => Some(Suit::Spades),
+            '-' => Some(Suit::None),
+            _ => None,
+        }
+    }
+}
+
+/// Base card number (1-54) for a rank and suit, ignoring which deck it came from
+fn base_card_for(rank: Rank, suit: Suit) -> Option<u8> {
+    if rank == Rank::Joker {
+        return Some(JOKER_BASE);
+    }
+    let suit_idx = SUITS_BY_BASE.iter().position(|&s| s == suit)? as u8;
+    Some(suit_idx * 13 + rank as u8)
+}
+
+impl CardId {
+    /// Which physical copy of this card this is: the deck number for ordinary cards, and a
+    /// count across decks for Jokers (deck 1 holds Jokers 1 and 2, deck 2 holds 3 and 4)
+    pub fn copy(&self) -> u8 {
+        let base = self.base_card();
+        if base >= JOKER_BASE {
+            (self.deck() - 1) * JOKERS_PER_DECK + (base - JOKER_BASE) + 1
+        } else {
+            self.deck()
+        }
+    }
+
+    /// Inverse of [`CardId::copy`]: the given copy of a rank and suit
+    pub fn from_copy(rank: Rank, suit: Suit, copy: u8) -> Result<Self> {
+        let base = base_card_for(rank, suit)
+            .ok_or_else(|| anyhow!("{:?} has no card of suit {:?}", rank, suit))?;
+        if copy == 0 {
+            return Err(anyhow!("Card copies are numbered from 1"));
+        }
+        if rank == Rank::Joker {
+            let idx = copy - 1;
+            CardId::try_new(base + idx % JOKERS_PER_DECK, idx / JOKERS_PER_DECK + 1)
+        } else {
+            CardId::try_new(base, copy)
+        }
+    }
+}
+
+fn write_card(f: &mut fmt::Formatter, rank: Rank, suit: Suit) -> fmt::Result {
+    if f.alternate() {
+        match rank {
+            Rank::Joker => write!(f, "Joker"),
+            _ => write!(f, "{} of {}", rank.name(), suit.name()),
+        }
+    } else {
+        write!(f, "{}{}", rank.symbol(), suit.letter())
+    }
+}
+
+/// "QH" by default, "Queen of hearts" with `{:#}`
+impl fmt::Display for CardView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_card(f, self.rank, self.suit)
+    }
+}
+
+impl CardView {
+    /// Concise designation with a suit symbol, e.g. "Q♥"
+    pub fn to_unicode(&self) -> String {
+        self.unicode().to_string()
+    }
+
+    pub fn unicode(&self) -> Unicode<'_, Self> {
+        Unicode(self)
+    }
+}
+
+impl CardId {
+    pub fn unicode(&self) -> Unicode<'_, Self> {
+        Unicode(self)
+    }
+}
+
+impl CardContainer {
+    pub fn unicode(&self) -> Unicode<'_, Self> {
+        Unicode(self)
+    }
+}
+
+/// Concise notation with suit symbols in place of letters: "Q♥" for a CardView, "Q♥#2" for a
+/// CardId, "Q♥ 2♣ J-" for a CardContainer. Made by their `unicode` methods.
+#[derive(Debug, Clone, Copy)]
+pub struct Unicode<'a, T>(pub &'a T);
+
+impl fmt::Display for Unicode<'_, CardView> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.0.rank.symbol(), self.0.suit.symbol())
+    }
+}
+
+impl fmt::Display for Unicode<'_, CardId> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}#{}", card_id_to_view(*self.0).unicode(), self.0.copy())
+    }
+}
+
+impl fmt::Display for Unicode<'_, CardContainer> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, card) in self.0.cards.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", card.unicode())?;
+        }
+        Ok(())
+    }
+}
+
+/// "QH#2" by default, "Queen of hearts #2" with `{:#}`
+impl fmt::Display for CardId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let view = card_id_to_view(*self);
+        write_card(f, view.rank, view.suit)?;
+        if f.alternate() {
+            write!(f, " #{}", self.copy())
+        } else {
+            write!(f, "#{}", self.copy())
+        }
+    }
+}
+
+/// Parses concise ("QH", "10d", "J-"), unicode ("Q♥") or long ("Queen of hearts", "Joker")
+/// notation, with an optional "#copy" suffix. Without one, the first copy is meant.
+impl FromStr for CardId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (card, copy) = match s.rsplit_once('#') {
+            Some((card, copy)) => {
+                let copy = copy.trim().parse::<u8>()
+                    .map_err(|_| anyhow!("Invalid copy number in {:?}", s))?;
+                (card.trim(), copy)
+            }
+            None => (s, 1),
+        };
+        let (rank, suit) = parse_card(card).ok_or_else(|| anyhow!("Invalid card {:?}", s))?;
+        CardId::from_copy(rank, suit, copy)
+    }
+}
+
+impl FromStr for CardView {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(card_id_to_view(s.parse()?))
+    }
+}
+
+fn parse_card(s: &str) -> Option<(Rank, Suit)> {
+    if s.eq_ignore_ascii_case("joker") {
+        return Some((Rank::Joker, Suit::None));
+    }
+
+    // Long form: "<rank> of <suit>"
+    if let Some((rank, suit)) = s.split_once(" of ") {
+        let rank = RANKS_BY_VALUE.iter()
+            .find(|r| r.name().eq_ignore_ascii_case(rank.trim()))?;
+        let suit = SUITS_BY_BASE.iter()
+            .find(|s| s.name().eq_ignore_ascii_case(suit.trim()))?;
+        return Some((*rank, *suit));
+    }
+
+    // Concise form: rank symbol followed by a single suit character
+    let suit_char = s.chars().last()?;
+    let rank = &s[..s.len() - suit_char.len_utf8()];
+    let suit = Suit::parse_char(suit_char)?;
+    if suit == Suit::None {
+        return rank.eq_ignore_ascii_case("J").then_some((Rank::Joker, Suit::None));
+    }
+    let rank = RANKS_BY_VALUE.iter()
+        .find(|r| r.symbol().eq_ignore_ascii_case(rank))?;
+    Some((*rank, suit))
+}
+
+/// "QH 2C J-" by default, "Queen of hearts, 2 of clubs, Joker" with `{:#}`
+impl fmt::Display for CardContainer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = if f.alternate() { ", " } else { " " };
+        for (idx, card) in self.cards.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "{}", separator)?;
+            }
+            fmt::Display::fmt(card, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses cards separated by whitespace, or by commas when using long names.
+/// Cards without a "#copy" take the next copy not already listed, so "5H 5H 5S" is valid.
+impl FromStr for CardContainer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens: Vec<&str> = if s.contains(',') {
+            s.split(',').map(str::trim).filter(|t| !t.is_empty()).collect()
+        } else {
+            s.split_whitespace().collect()
+        };
+
+        let mut cards: Vec<CardView> = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let mut id: CardId = token.parse()?;
+            if !token.contains('#') {
+                let view = card_id_to_view(id);
+                while cards.iter().any(|card| card.id == id) {
+                    id = CardId::from_copy(view.rank, view.suit, id.copy() + 1)?;
+                }
+            } else if cards.iter().any(|card| card.id == id) {
+                return Err(anyhow!("Card {} is listed twice", id));
+            }
+            cards.push(card_id_to_view(id));
+        }
+        Ok(CardContainer { cards })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unicode_renders_every_type_and_parses_back() {
+        let cards: CardContainer = "QH#2 10D 2C J-#3".parse().unwrap();
+        assert_eq!(cards.unicode().to_string(), "Q♥ 10♦ 2♣ J-");
+        assert_eq!(cards.cards[0].unicode().to_string(), "Q♥");
+        assert_eq!(cards.cards[0].id.unicode().to_string(), "Q♥#2");
+        assert_eq!(cards.cards[3].id.unicode().to_string(), "J-#3");
+
+        for card in &cards.cards {
+            assert_eq!(card.id.unicode().to_string().parse::<CardId>().unwrap(), card.id);
+        }
+    }
+}
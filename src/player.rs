@@ -40,12 +40,39 @@ pub struct TurnResult {
 #[derive(Debug, Clone)]
 pub struct PlayerView {
     pub current_player: usize,  // who's turn it is. 0 means mine, 1 is player to my left etc
+    pub seat: usize,            // My absolute seat, as used in container ids (Hand, Group, Run)
     pub contract: crate::contract::ContractOrder,
     pub held_cards: crate::card::CardContainer,           // Player's own cards
-    pub table_groups: Vec<crate::card::CardContainer>, // All laid down groups  
-    pub table_runs: Vec<crate::card::CardContainer>,   // All laid down runs
-    pub last_discard: CardView, // Top of discard pile (none if "dead")
-    // TODO: info about other players (nunu requests, held hand size, laid down)
+    pub table_groups: Vec<TableSet>, // All laid down groups
+    pub table_runs: Vec<TableSet>,   // All laid down runs
+    pub last_discard: Option<CardView>, // Top of discard pile (none if "dead")
+    pub last_nunu: Option<NunuReport>,  // Who wanted the latest discard
+    pub players_laid_down: Vec<bool>,   // By relative seat: 0 is me
+    // TODO: held hand size of the other players
     // TODO: discard ledger
     // TODO: scores
-}
\ No newline at end of file
+}
+
+/// A laid-down set as seen from the table: the container holding it and its cards
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSet {
+    pub id: crate::card::ContainerId,
+    pub cards: crate::card::CardContainer,
+}
+
+/// Who asked for a discard out of turn, and who got it. Seats are relative to the viewer,
+/// like `PlayerView::current_player`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NunuReport {
+    pub discarder: usize,
+    pub card: CardView,
+    pub asked: Vec<usize>,         // In polling order, starting left of the discarder
+    pub claimed_by: Option<usize>, // The first to ask
+}
+
+impl NunuReport {
+    /// Players who asked but were beaten to the card
+    pub fn preempted(&self) -> &[usize] {
+        self.asked.get(1..).unwrap_or_default()
+    }
+}
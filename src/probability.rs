@@ -0,0 +1,139 @@
+use anyhow::{Result, anyhow};
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use crate::card::CardContainer;
+use crate::contract::ContractOrder;
+use crate::solver::{distance, kind_of, Kind, KindCounts, JOKER, KINDS};
+
+// Chance of being able to lay down after drawing more cards.
+//
+// Draws are modelled as a uniformly random selection, without replacement, from the cards the
+// player can't see. Discards along the way are ignored: a bid only ever needs a subset of the
+// cards held, so the question is whether the hand plus every card drawn holds one.
+
+/// How to work out the probability
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Estimate {
+    /// Sums over every combination of draws; cost grows quickly with the number of draws
+    Exact,
+    /// Samples random draws
+    MonteCarlo { trials: usize },
+}
+
+impl Estimate {
+    /// Most draws the exact calculation is used for by [`Estimate::for_draws`]
+    pub const EXACT_MAX_DRAWS: usize = 3;
+    pub const DEFAULT_TRIALS: usize = 10_000;
+    
+    /// Exact for a few draws, sampled beyond that
+    pub fn for_draws(draws: usize) -> Self {
+        if draws <= Self::EXACT_MAX_DRAWS {
+            Estimate::Exact
+        } else {
+            Estimate::MonteCarlo { trials: Self::DEFAULT_TRIALS }
+        }
+    }
+}
+
+fn kind_counts(cards: &CardContainer) -> KindCounts {
+    let mut counts = [0; KINDS];
+    for card in &cards.cards {
+        counts[kind_of(card.id) as usize] += 1;
+    }
+    counts
+}
+
+/// Probability that `hand` can lay down `contract` after drawing `draws` cards from `unseen`,
+/// the cards not visible to the player
+pub fn lay_down_probability(
+    hand: &CardContainer,
+    contract: &ContractOrder,
+    unseen: &CardContainer,
+    draws: usize,
+    estimate: Estimate,
+) -> Result<f64> {
+    if draws > unseen.cards.len() {
+        return Err(anyhow!("Cannot draw {} cards from {} unseen", draws, unseen.cards.len()));
+    }
+    
+    let mut held = kind_counts(hand);
+    let unseen_counts = kind_counts(unseen);
+    match estimate {
+        Estimate::Exact => {
+            let combinations = exact_successes(&mut held, &unseen_counts, 1, draws, contract);
+            Ok(combinations / binomial(unseen.cards.len(), draws))
+        }
+        Estimate::MonteCarlo { trials } => {
+            if trials == 0 {
+                return Err(anyhow!("Monte Carlo estimate needs at least one trial"));
+            }
+            Ok(sampled_successes(&held, unseen, draws, trials, contract) as f64 / trials as f64)
+        }
+    }
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Number of ways to draw `draws` more cards, all of kind `from` or above, that let `held`
+/// lay down. Each way is counted by the smallest kind drawn and how many of it, with the
+/// rest drawn from the kinds above.
+fn exact_successes(
+    held: &mut KindCounts,
+    unseen: &KindCounts,
+    from: Kind,
+    draws: usize,
+    contract: &ContractOrder,
+) -> f64 {
+    let needed = distance(held, contract);
+    if needed == 0 {
+        // Whatever comes next, the hand can already lay down
+        let remaining: usize = unseen[from as usize..].iter().map(|&count| count as usize).sum();
+        return binomial(remaining, draws);
+    }
+    if needed > draws {
+        return 0.0;
+    }
+    
+    let mut total = 0.0;
+    for kind in from..=JOKER {
+        let available = unseen[kind as usize] as usize;
+        for count in 1..=available.min(draws) {
+            held[kind as usize] += count as u8;
+            let rest = exact_successes(held, unseen, kind + 1, draws - count, contract);
+            held[kind as usize] -= count as u8;
+            total += binomial(available, count) * rest;
+        }
+    }
+    total
+}
+
+/// Number of `trials` random draws that let `held` lay down
+fn sampled_successes(
+    held: &KindCounts,
+    unseen: &CardContainer,
+    draws: usize,
+    trials: usize,
+    contract: &ContractOrder,
+) -> usize {
+    if distance(held, contract) > draws {
+        return 0;
+    }
+    
+    let mut kinds: Vec<Kind> = unseen.cards.iter().map(|card| kind_of(card.id)).collect();
+    let mut rng = thread_rng();
+    (0..trials)
+        .filter(|_| {
+            let (drawn, _) = kinds.partial_shuffle(&mut rng, draws);
+            let mut after = *held;
+            for &kind in drawn.iter() {
+                after[kind as usize] += 1;
+            }
+            distance(&after, contract) == 0
+        })
+        .count()
+}
@@ -0,0 +1,236 @@
+use crate::card::{CardContainer, CardView, ContainerId, SetError, SetType};
+use crate::contract::ContractOrder;
+use crate::player::{CardMove, TableSet};
+
+// Table rearrangement: placing cards on the table when no single-card play will take them.
+//
+// Per IMPL.md, the table may pass through broken states during a turn so long as every bid is
+// valid at the end. The search works on final configurations rather than individual moves: it
+// pulls some table cards loose, then deals the pulled cards and the new ones out over the same
+// containers until every set is valid again. Fewer pulled cards are tried first (iterative
+// deepening), so the first configuration found disturbs the table as little as possible.
+//
+// A player may only take cards out of their own sets. Everyone's sets can receive cards.
+
+/// A valid table that takes the new cards, and how to get there
+#[derive(Debug, Clone)]
+pub struct Rearrangement {
+    /// Every set after the rearrangement, in the order given
+    pub table: Vec<TableSet>,
+    /// Pulled table cards go to the temp container first, then everything goes to its set
+    pub moves: Vec<CardMove>,
+}
+
+/// Where a card starts out: a table set (by index) or the hand
+type Origin = Option<usize>;
+
+/// Finds a table that places every one of `cards` without creating or destroying a set,
+/// pulling at most `max_pulled` cards out of their sets along the way. Only sets laid down by
+/// `acting_seat` (an absolute seat, as in the set ids) give up cards; the rest can only gain.
+/// The cost grows quickly with `max_pulled`; a handful is plenty for real tables.
+pub fn find_rearrangement<'a>(
+    table: impl IntoIterator<Item = &'a TableSet>,
+    cards: &[CardView],
+    contract: &ContractOrder,
+    acting_seat: usize,
+    max_pulled: usize,
+) -> Option<Rearrangement> {
+    let sets: Vec<(&TableSet, SetType)> = table.into_iter()
+        .filter_map(|set| set.id.set_type().map(|set_type| (set, set_type)))
+        .collect();
+    // Cards that may be pulled, by set index
+    let table_cards: Vec<(usize, &CardView)> = sets.iter().enumerate()
+        .filter(|(_, (set, _))| set.id.owner() == Some(acting_seat))
+        .flat_map(|(idx, (set, _))| set.cards.cards.iter().map(move |card| (idx, card)))
+        .collect();
+    let search = Search { sets: &sets, contract };
+    
+    for pulled_count in 0..=max_pulled.min(table_cards.len()) {
+        let mut found = None;
+        each_combination(table_cards.len(), pulled_count, &mut Vec::new(), &mut |pulled| {
+            found = search.try_pulling(&table_cards, pulled, cards);
+            found.is_none()
+        });
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+struct Search<'s, 'a> {
+    sets: &'s [(&'a TableSet, SetType)],
+    contract: &'s ContractOrder,
+}
+
+impl Search<'_, '_> {
+    /// Pulls the given table cards and tries to deal them, with the new cards, back out
+    fn try_pulling(
+        &self,
+        table_cards: &[(usize, &CardView)],
+        pulled: &[usize],
+        cards: &[CardView],
+    ) -> Option<Rearrangement> {
+        let mut containers: Vec<CardContainer> = self.sets.iter()
+            .map(|(set, _)| set.cards.clone())
+            .collect();
+        for &idx in pulled {
+            let (set_idx, card) = table_cards[idx];
+            containers[set_idx].cards.retain(|kept| kept.id != card.id);
+        }
+        
+        let mut free: Vec<(&CardView, Origin)> = pulled.iter()
+            .map(|&idx| (table_cards[idx].1, Some(table_cards[idx].0)))
+            .chain(cards.iter().map(|card| (card, None)))
+            .collect();
+        // Identical cards next to each other, so their placements can be kept in order
+        free.sort_by_key(|(card, _)| (card.rank as u8, card.suit as u8));
+        
+        let mut placement = Vec::with_capacity(free.len());
+        if !self.place(&free, &mut containers, &mut placement) {
+            return None;
+        }
+        Some(self.rearrangement(&free, &placement, containers))
+    }
+    
+    /// Deals the free cards out over the containers, one at a time, until every set is valid
+    fn place(
+        &self,
+        free: &[(&CardView, Origin)],
+        containers: &mut [CardContainer],
+        placement: &mut Vec<usize>,
+    ) -> bool {
+        let idx = placement.len();
+        let Some(&(card, _)) = free.get(idx) else {
+            return self.sets.iter().zip(containers.iter())
+                .all(|(&(_, set_type), container)| self.contract.validate_set(&set_type, container));
+        };
+        
+        // Swapping two identical cards gives the same table
+        let same_as_previous = idx > 0 && {
+            let previous = free[idx - 1].0;
+            (previous.rank, previous.suit) == (card.rank, card.suit)
+        };
+        let first = if same_as_previous { placement[idx - 1] } else { 0 };
+        
+        for target in first..containers.len() {
+            containers[target].cards.push(card.clone());
+            if could_become_valid(self.sets[target].1, &containers[target]) {
+                placement.push(target);
+                if self.place(free, containers, placement) {
+                    return true;
+                }
+                placement.pop();
+            }
+            containers[target].cards.pop();
+        }
+        false
+    }
+    
+    fn rearrangement(
+        &self,
+        free: &[(&CardView, Origin)],
+        placement: &[usize],
+        containers: Vec<CardContainer>,
+    ) -> Rearrangement {
+        let moved: Vec<(&CardView, Origin, usize)> = free.iter().zip(placement)
+            .map(|(&(card, origin), &target)| (card, origin, target))
+            .filter(|&(_, origin, target)| origin != Some(target))
+            .collect();
+        
+        let pulls = moved.iter()
+            .filter(|(_, origin, _)| origin.is_some())
+            .map(|(card, _, _)| CardMove { card_id: card.id, to_container: ContainerId::TableTemp });
+        let placements = moved.iter()
+            .map(|&(card, _, target)| CardMove { card_id: card.id, to_container: self.sets[target].0.id });
+        let moves = pulls.chain(placements).collect();
+        
+        let table = self.sets.iter().zip(containers)
+            .map(|(&(set, _), cards)| TableSet { id: set.id, cards })
+            .collect();
+        Rearrangement { table, moves }
+    }
+}
+
+/// True if adding cards could still make this a valid set: too few cards, a gap in a run, or
+/// no natural yet can all be fixed that way
+fn could_become_valid(set_type: SetType, cards: &CardContainer) -> bool {
+    matches!(
+        set_type.check(cards, 0),
+        Ok(()) | Err(SetError::TooSmall { .. } | SetError::NotContiguous | SetError::NoNaturalCard)
+    )
+}
+
+/// Calls `visit` with every `k`-element subset of 0..n, in increasing order, until it returns false
+fn each_combination(
+    n: usize,
+    k: usize,
+    chosen: &mut Vec<usize>,
+    visit: &mut dyn FnMut(&[usize]) -> bool,
+) -> bool {
+    if chosen.len() == k {
+        return visit(chosen);
+    }
+    let start = chosen.last().map_or(0, |&last| last + 1);
+    for idx in start..=(n - (k - chosen.len())) {
+        chosen.push(idx);
+        let keep_going = each_combination(n, k, chosen, visit);
+        chosen.pop();
+        if !keep_going {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn set(id: ContainerId, cards: &str) -> TableSet {
+        TableSet { id, cards: cards.parse().unwrap() }
+    }
+    
+    /// Seat 1's run needs the 7H from seat 1's own group to take the 8H
+    fn table() -> Vec<TableSet> {
+        vec![
+            set(ContainerId::Group(0, 0), "KC KD KS"),
+            set(ContainerId::Run(0, 0), "9S 10S JS QS"),
+            set(ContainerId::Group(1, 0), "7C 7D 7S 7H"),
+            set(ContainerId::Run(1, 0), "3H 4H 5H 6H"),
+        ]
+    }
+    
+    #[test]
+    fn pulls_from_the_players_own_set() {
+        let (table, contract) = (table(), "GR:10".parse().unwrap());
+        let new: CardContainer = "8H".parse().unwrap();
+        assert!(find_rearrangement(&table, &new.cards, &contract, 1, 0).is_none());
+        
+        let found = find_rearrangement(&table, &new.cards, &contract, 1, 2).unwrap();
+        assert_eq!(found.table[2].cards.to_string(), "7C 7D 7S");
+        assert_eq!(found.table[3].cards.cards.len(), 6);
+        let pulled: Vec<_> = found.moves.iter()
+            .filter(|card_move| card_move.to_container == ContainerId::TableTemp)
+            .map(|card_move| card_move.card_id)
+            .collect();
+        assert_eq!(pulled, vec![table[2].cards.cards[3].id]);
+    }
+    
+    #[test]
+    fn other_seats_sets_are_never_pulled_from() {
+        let (table, contract) = (table(), "GR:10".parse().unwrap());
+        let new: CardContainer = "8H".parse().unwrap();
+        assert!(find_rearrangement(&table, &new.cards, &contract, 0, 4).is_none());
+        
+        // Even with pulls to spare, seat 1 only takes cards out of its own sets
+        let found = find_rearrangement(&table, &new.cards, &contract, 1, 4).unwrap();
+        let pulls = found.moves.iter().filter(|card_move| card_move.to_container == ContainerId::TableTemp);
+        for card_move in pulls {
+            let source = table.iter()
+                .find(|set| set.cards.cards.iter().any(|card| card.id == card_move.card_id))
+                .unwrap();
+            assert_eq!(source.id.owner(), Some(1));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Result, anyhow};
+use crate::card::{card_id_to_rank, CardId, Rank};
+
+/// Penalty points for each card left in hand when a hand ends. Cards not listed here score
+/// their face value. The standard table follows WHAT.md; house variants can change any entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoringTable {
+    pub ace: u32,
+    pub two: u32,
+    pub ten_and_face: u32, // 10, Jack, Queen, King
+    pub joker: u32,
+}
+
+impl ScoringTable {
+    pub const STANDARD: ScoringTable = ScoringTable { ace: 20, two: 20, ten_and_face: 10, joker: 50 };
+    
+    /// Points for one card of the given rank
+    pub const fn rank_points(&self, rank: Rank) -> u32 {
+        match rank {
+            Rank::Ace => self.ace,
+            Rank::Two => self.two,
+            Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => self.ten_and_face,
+            Rank::Joker => self.joker,
+            rank => rank as u32,
+        }
+    }
+    
+    pub fn card_points(&self, card: CardId) -> u32 {
+        self.rank_points(card_id_to_rank(card))
+    }
+    
+    pub fn hand_points(&self, cards: &[CardId]) -> u32 {
+        cards.iter().map(|&card| self.card_points(card)).sum()
+    }
+}
+
+impl Default for ScoringTable {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// How a hand ended, by seat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandOutcome {
+    pub winner: Option<usize>, // Seat that went out; None if nobody did
+    pub points: Vec<u32>,      // Penalty for the cards each seat still held
+    pub turns: usize,
+    pub laid_down: Vec<bool>,
+}
+
+/// Running totals over the hands of a game. Lower is better.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoresheet {
+    hands: Vec<HandOutcome>,
+    totals: Vec<u32>,
+}
+
+impl Scoresheet {
+    pub fn new(num_players: usize) -> Self {
+        Self { hands: Vec::new(), totals: vec![0; num_players] }
+    }
+    
+    pub fn record(&mut self, outcome: HandOutcome) -> Result<()> {
+        if outcome.points.len() != self.totals.len() {
+            return Err(anyhow!(
+                "Hand scored {} players, but the scoresheet has {}",
+                outcome.points.len(),
+                self.totals.len()
+            ));
+        }
+        for (total, points) in self.totals.iter_mut().zip(&outcome.points) {
+            *total += points;
+        }
+        self.hands.push(outcome);
+        Ok(())
+    }
+    
+    pub fn hands(&self) -> &[HandOutcome] {
+        &self.hands
+    }
+    
+    /// Total points for each seat so far
+    pub fn totals(&self) -> &[u32] {
+        &self.totals
+    }
+    
+    /// Seats with their totals, best (lowest) first; ties keep seat order
+    pub fn standings(&self) -> Vec<(usize, u32)> {
+        let mut standings: Vec<(usize, u32)> = self.totals.iter().copied().enumerate().collect();
+        standings.sort_by_key(|&(_, total)| total);
+        standings
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+use crate::card::{card_id_to_view, CardContainer, CardId, Rank, SetType, Suit};
+use crate::contract::{ContractBid, ContractOrder};
+
+// Bid solver: finds the ways a hand can fulfil a contract.
+//
+// Cards that are interchangeable for set building are grouped into "kinds": one kind per base
+// card, with both Jokers sharing a kind. Candidate sets are built from kind counts, which keeps
+// the two copies of a card (or the two Jokers) from producing duplicate bids. Concrete cards are
+// only picked once a full bid has been found.
+
+/// Interchangeable cards: the base card (1-52), or JOKER for any Joker
+pub(crate) type Kind = u8;
+pub(crate) const JOKER: Kind = 53;
+pub(crate) const KINDS: usize = 54; // Index 0 unused
+
+/// Some number of cards of one kind
+type KindCount = (Kind, u8);
+
+/// Cards held of each kind
+pub(crate) type KindCounts = [u8; KINDS];
+
+pub(crate) fn kind_of(card: CardId) -> Kind {
+    card.base_card().min(JOKER)
+}
+
+/// Kind at a run position (1 and 14 are both Ace) in suit 0-3
+fn suited_kind(suit_idx: u8, pos: u8) -> Kind {
+    suit_idx * 13 + (pos - 1) % 13 + 1
+}
+
+/// A group or run, as counts of each kind it uses
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Candidate {
+    set_type: SetType,
+    kinds: Vec<KindCount>, // Sorted by kind, counts above zero
+}
+
+impl Candidate {
+    fn size(&self) -> usize {
+        selection_size(&self.kinds)
+    }
+    
+    fn fits(&self, available: &KindCounts) -> bool {
+        self.kinds.iter().all(|&(kind, count)| available[kind as usize] >= count)
+    }
+    
+    fn take(&self, available: &mut KindCounts) {
+        for &(kind, count) in &self.kinds {
+            available[kind as usize] -= count;
+        }
+    }
+    
+    fn give_back(&self, available: &mut KindCounts) {
+        for &(kind, count) in &self.kinds {
+            available[kind as usize] += count;
+        }
+    }
+}
+
+/// A hand sorted by kind, so bids found over kinds can be turned back into cards
+struct Pool {
+    cards: Vec<Vec<CardId>>, // Indexed by kind
+    counts: KindCounts,
+}
+
+impl Pool {
+    fn new(hand: &CardContainer) -> Self {
+        let mut cards = vec![Vec::new(); KINDS];
+        let mut counts = [0; KINDS];
+        for card in &hand.cards {
+            let kind = kind_of(card.id);
+            cards[kind as usize].push(card.id);
+            counts[kind as usize] += 1;
+        }
+        Self { cards, counts }
+    }
+    
+    /// Picks concrete cards for each chosen candidate, in order
+    fn materialize(&self, chosen: &[&Candidate]) -> ContractBid {
+        let mut used = [0usize; KINDS];
+        let mut bid = ContractBid::new(Vec::new(), Vec::new());
+        for candidate in chosen {
+            let mut cards = Vec::new();
+            for &(kind, count) in &candidate.kinds {
+                let kind = kind as usize;
+                let picked = &self.cards[kind][used[kind]..used[kind] + count as usize];
+                cards.extend(picked.iter().copied().map(card_id_to_view));
+                used[kind] += count as usize;
+            }
+            let container = CardContainer { cards };
+            match candidate.set_type {
+                SetType::Group => bid.groups.push(container),
+                SetType::Run => bid.runs.push(container),
+            }
+        }
+        bid
+    }
+}
+
+/// Calls `visit` with every way of taking 0..=max of each option (all zero included)
+fn each_selection(
+    options: &[KindCount],
+    picked: &mut Vec<KindCount>,
+    visit: &mut dyn FnMut(&[KindCount]),
+) {
+    let Some((&(kind, max), rest)) = options.split_first() else {
+        visit(picked);
+        return;
+    };
+    each_selection(rest, picked, visit);
+    for count in 1..=max {
+        picked.push((kind, count));
+        each_selection(rest, picked, visit);
+        picked.pop();
+    }
+}
+
+fn selection_size(selection: &[KindCount]) -> usize {
+    selection.iter().map(|&(_, count)| count as usize).sum()
+}
+
+fn candidate(set_type: SetType, parts: &[&[KindCount]]) -> Candidate {
+    let mut kinds: Vec<KindCount> = parts.iter().flat_map(|part| part.iter().copied()).collect();
+    kinds.sort_unstable();
+    // The same kind can come from two parts (a 2 as itself and as a wild): merge them
+    kinds.dedup_by(|next, prev| {
+        let same = next.0 == prev.0;
+        if same {
+            prev.1 += next.1;
+        }
+        same
+    });
+    kinds.retain(|&(_, count)| count > 0);
+    Candidate { set_type, kinds }
+}
+
+/// What the cards of a candidate set must come to
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// A whole set of at least this many cards, for bids
+    Complete(usize),
+    /// Held cards towards a set of exactly this many, the other places to be drawn
+    Partial(usize),
+}
+
+impl Target {
+    /// True if a group of `size` held cards, `naturals` of them natural, is a candidate.
+    /// A partial group with no natural keeps a place free for a natural to be drawn.
+    fn fits_group(self, naturals: usize, size: usize) -> bool {
+        match self {
+            Target::Complete(min) => naturals > 0 && size >= min,
+            Target::Partial(min) if naturals == 0 => size < min,
+            Target::Partial(min) => size <= min,
+        }
+    }
+    
+    /// Run lengths to try
+    fn run_lengths(self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            Target::Complete(min) => min.max(1)..=13,
+            Target::Partial(min) => min.clamp(1, 13)..=min.clamp(1, 13),
+        }
+    }
+    
+    /// True if `size` held cards are a candidate for a run of `len` places
+    fn fits_run(self, len: usize, size: usize) -> bool {
+        match self {
+            Target::Complete(_) => size == len,
+            Target::Partial(_) => size <= len,
+        }
+    }
+}
+
+/// Every group the held cards can form, or go towards: naturals of one rank plus any wilds
+fn group_candidates(held: &KindCounts, target: Target) -> Vec<Candidate> {
+    let available = |kinds: &mut dyn Iterator<Item = Kind>| -> Vec<KindCount> {
+        kinds.map(|kind| (kind, held[kind as usize])).filter(|&(_, count)| count > 0).collect()
+    };
+    let twos = available(&mut (0..4).map(|suit| suited_kind(suit, 2)));
+    let jokers = available(&mut std::iter::once(JOKER));
+    let all_wilds: Vec<KindCount> = twos.iter().chain(&jokers).copied().collect();
+    
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    // Rank 2 is the group of 2s: its only wilds are Jokers
+    for rank in 1..=13 {
+        let (naturals, wilds) = match rank {
+            2 => (twos.clone(), &jokers),
+            _ => (available(&mut (0..4).map(|suit| suited_kind(suit, rank))), &all_wilds),
+        };
+        
+        each_selection(&naturals, &mut Vec::new(), &mut |nat| {
+            each_selection(wilds, &mut Vec::new(), &mut |wild| {
+                let natural_count = selection_size(nat);
+                if target.fits_group(natural_count, natural_count + selection_size(wild)) {
+                    let group = candidate(SetType::Group, &[nat, wild]);
+                    if seen.insert(group.kinds.clone()) {
+                        candidates.push(group);
+                    }
+                }
+            });
+        });
+    }
+    candidates
+}
+
+/// Every run the held cards can form, or go towards, over all suits, spans and wild
+/// placements. Places a partial run leaves unfilled are taken to be the naturals that
+/// belong there.
+fn run_candidates(held: &KindCounts, target: Target) -> Vec<Candidate> {
+    let jokers = held[JOKER as usize];
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    
+    for suit in 0..4 {
+        let two = suited_kind(suit, 2);
+        let twos = held[two as usize];
+        
+        for len in target.run_lengths() {
+            for low in 1..=(15 - len as u8) {
+                // Positions whose natural card we hold (at position 2, that's the 2 itself)
+                let naturals: Vec<Kind> = (low..low + len as u8)
+                    .map(|pos| suited_kind(suit, pos))
+                    .filter(|&kind| held[kind as usize] > 0)
+                    .collect();
+                
+                for mask in 0u32..(1 << naturals.len()) {
+                    let used: Vec<KindCount> = naturals.iter().enumerate()
+                        .filter(|(bit, _)| mask & (1 << bit) != 0)
+                        .map(|(_, &kind)| (kind, 1))
+                        .collect();
+                    let gaps = (len - used.len()) as u8;
+                    let spare_twos = twos - used.iter().any(|&(kind, _)| kind == two) as u8;
+                    
+                    for joker_count in 0..=jokers.min(gaps) {
+                        for two_count in 0..=spare_twos.min(gaps - joker_count) {
+                            let size = used.len() + (joker_count + two_count) as usize;
+                            // Nothing but Jokers and no places left: there's no suit
+                            let all_jokers = used.is_empty() && two_count == 0 && joker_count as usize == len;
+                            if !target.fits_run(len, size) || all_jokers {
+                                continue;
+                            }
+                            let wilds = [(JOKER, joker_count), (two, two_count)];
+                            let run = candidate(SetType::Run, &[&used, &wilds]);
+                            if seen.insert(run.kinds.clone()) {
+                                candidates.push(run);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// Candidate sets for each set type
+struct Candidates {
+    groups: Vec<Candidate>,
+    runs: Vec<Candidate>,
+}
+
+impl Candidates {
+    fn new(held: &KindCounts, contract: &ContractOrder) -> Self {
+        Self {
+            groups: group_candidates(held, Target::Complete(contract.min_set_size(&SetType::Group))),
+            runs: run_candidates(held, Target::Complete(contract.min_set_size(&SetType::Run))),
+        }
+    }
+    
+    fn of(&self, set_type: &SetType) -> &[Candidate] {
+        match set_type {
+            SetType::Group => &self.groups,
+            SetType::Run => &self.runs,
+        }
+    }
+    
+    /// Partial candidates for each set type, largest first
+    fn partial(held: &KindCounts, contract: &ContractOrder) -> Self {
+        let mut candidates = Self {
+            groups: group_candidates(held, Target::Partial(contract.min_set_size(&SetType::Group))),
+            runs: run_candidates(held, Target::Partial(contract.min_set_size(&SetType::Run))),
+        };
+        candidates.groups.sort_by_key(|c| std::cmp::Reverse(c.size()));
+        candidates.runs.sort_by_key(|c| std::cmp::Reverse(c.size()));
+        candidates
+    }
+    
+    fn largest(&self, set_type: &SetType) -> usize {
+        self.of(set_type).first().map_or(0, Candidate::size)
+    }
+}
+
+/// The sets a contract asks for, groups first
+fn contract_slots(contract: &ContractOrder) -> Vec<SetType> {
+    let mut slots = vec![SetType::Group; contract.required_groups()];
+    slots.extend(vec![SetType::Run; contract.required_runs()]);
+    slots
+}
+
+/// Walks every combination of candidates filling `slots` that fits in `available`.
+/// Sets of the same type are chosen in candidate order, so each combination appears once.
+/// `visit` returns false to stop the search early.
+fn search<'a>(
+    candidates: &'a Candidates,
+    slots: &[SetType],
+    start: usize,
+    available: &mut KindCounts,
+    chosen: &mut Vec<&'a Candidate>,
+    visit: &mut dyn FnMut(&[&'a Candidate]) -> bool,
+) -> bool {
+    let Some((set_type, rest)) = slots.split_first() else {
+        return visit(chosen);
+    };
+    
+    for (idx, candidate) in candidates.of(set_type).iter().enumerate().skip(start) {
+        if !candidate.fits(available) {
+            continue;
+        }
+        let next_start = match rest.first() {
+            Some(next) if next == set_type => idx,
+            _ => 0,
+        };
+        candidate.take(available);
+        chosen.push(candidate);
+        let keep_going = search(candidates, rest, next_start, available, chosen, visit);
+        chosen.pop();
+        candidate.give_back(available);
+        if !keep_going {
+            return false;
+        }
+    }
+    true
+}
+
+/// Every bid the hand can lay down for the contract, most points shed first
+pub fn find_bids(hand: &CardContainer, contract: &ContractOrder) -> Vec<ContractBid> {
+    let pool = Pool::new(hand);
+    let candidates = Candidates::new(&pool.counts, contract);
+    
+    let mut bids = Vec::new();
+    let mut available = pool.counts;
+    let slots = contract_slots(contract);
+    search(&candidates, &slots, 0, &mut available, &mut Vec::new(), &mut |chosen| {
+        bids.push(pool.materialize(chosen));
+        true
+    });
+    
+    bids.sort_by_key(|bid| std::cmp::Reverse(bid.points()));
+    bids
+}
+
+// Contract deficiency: how many more cards a hand needs before it can lay down.
+//
+// Each set is aimed at exactly the contract's minimum size (a longer set never needs fewer
+// draws). A partial candidate is the held cards going towards one such set; the rest of its
+// places are cards still to be drawn, so every held card used is one less card to draw. The
+// distance is then the contract's total minimum size less the most held cards that can be
+// spread over its sets without sharing any.
+
+/// How far a hand is from laying down a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deficiency {
+    /// Fewest extra cards needed to lay down (0 if the hand already can)
+    pub distance: usize,
+    /// Every card that would bring the distance down if drawn, Joker included
+    pub outs: Vec<(Rank, Suit)>,
+}
+
+/// Raises `best` to the most held cards that can be spread over `slots`. Candidates are
+/// largest first, so a branch stops as soon as it can no longer beat `best`.
+fn most_cards_used(
+    candidates: &Candidates,
+    slots: &[SetType],
+    start: usize,
+    available: &mut KindCounts,
+    used: usize,
+    best: &mut usize,
+) {
+    let Some((set_type, rest)) = slots.split_first() else {
+        *best = (*best).max(used);
+        return;
+    };
+    let rest_bound: usize = rest.iter().map(|slot| candidates.largest(slot)).sum();
+    
+    for (idx, candidate) in candidates.of(set_type).iter().enumerate().skip(start) {
+        if used + candidate.size() + rest_bound <= *best {
+            break;
+        }
+        if !candidate.fits(available) {
+            continue;
+        }
+        let next_start = match rest.first() {
+            Some(next) if next == set_type => idx,
+            _ => 0,
+        };
+        candidate.take(available);
+        most_cards_used(candidates, rest, next_start, available, used + candidate.size(), best);
+        candidate.give_back(available);
+    }
+}
+
+/// Fewest extra cards needed, over kind counts
+pub(crate) fn distance(held: &KindCounts, contract: &ContractOrder) -> usize {
+    let slots = contract_slots(contract);
+    let needed: usize = slots.iter().map(|slot| contract.min_set_size(slot)).sum();
+    
+    let candidates = Candidates::partial(held, contract);
+    let mut best = 0;
+    most_cards_used(&candidates, &slots, 0, &mut held.clone(), 0, &mut best);
+    needed - best
+}
+
+/// Fewest extra cards the hand needs before it can lay down the contract, assuming any card
+/// could still be drawn. Zero exactly when [`find_bids`] finds a bid.
+pub fn contract_distance(hand: &CardContainer, contract: &ContractOrder) -> usize {
+    distance(&Pool::new(hand).counts, contract)
+}
+
+/// The hand's distance from the contract, and the cards that would shorten it
+pub fn contract_deficiency(hand: &CardContainer, contract: &ContractOrder) -> Deficiency {
+    let mut held = Pool::new(hand).counts;
+    let current = distance(&held, contract);
+    
+    let mut outs = Vec::new();
+    if current > 0 {
+        for kind in 1..=JOKER {
+            held[kind as usize] += 1;
+            if distance(&held, contract) < current {
+                let view = card_id_to_view(CardId::new(kind, 1));
+                outs.push((view.rank, view.suit));
+            }
+            held[kind as usize] -= 1;
+        }
+    }
+    Deficiency { distance: current, outs }
+}
@@ -11,6 +11,12 @@ impl BasicPlayer {
     }
 }
 
+impl Default for BasicPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Player for BasicPlayer {
     fn check_nunu(&mut self, _view: &PlayerView, _discarded_card: &CardView) -> bool {
         // Never nunu for basic player
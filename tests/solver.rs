@@ -0,0 +1,72 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rumminator::card::{card_id_to_view, CardContainer, CardId, DeckConfig};
+use rumminator::contract::{ContractBid, ContractOrder};
+use rumminator::solver::contract_distance;
+
+fn hand(s: &str) -> CardContainer {
+    s.parse().unwrap()
+}
+
+fn contract(s: &str) -> ContractOrder {
+    s.parse().unwrap()
+}
+
+/// Random hands of `size` cards from a two-deck shoe, the same every run
+fn random_hands(count: usize, size: usize) -> Vec<CardContainer> {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut shoe: Vec<CardId> = DeckConfig::for_players(4).cards().collect();
+    (0..count)
+        .map(|_| {
+            let (drawn, _) = shoe.partial_shuffle(&mut rng, size);
+            CardContainer { cards: drawn.iter().copied().map(card_id_to_view).collect() }
+        })
+        .collect()
+}
+
+fn assert_bids_valid(hand: &CardContainer, contract: &ContractOrder) {
+    for bid in ContractBid::find_all(hand, contract) {
+        if let Err(reason) = bid.validate(contract, hand) {
+            panic!("{:?} for {} from {}: {}", bid, contract, hand, reason);
+        }
+    }
+}
+
+#[test]
+fn all_joker_runs_are_not_bids() {
+    let jokers = hand("J- J- J- J- 5H 6H 7H 8H");
+    assert_bids_valid(&jokers, &contract("RR:8"));
+    assert!(!ContractBid::find_all(&jokers, &contract("RR:8")).is_empty());
+    
+    let jokers = hand("J- J- J- J- 9C");
+    assert_bids_valid(&jokers, &contract("R:5"));
+    assert!(!ContractBid::find_all(&jokers, &contract("R:5")).is_empty());
+}
+
+#[test]
+fn every_bid_found_is_valid() {
+    for contract in ["GG:10", "GR:10", "RR:12", "GGR:12", "RRR:12"].map(contract) {
+        for hand in random_hands(200, contract.hand_size()) {
+            assert_bids_valid(&hand, &contract);
+        }
+    }
+}
+
+#[test]
+fn distance_is_zero_exactly_when_a_bid_is_found() {
+    let check = |hand: &CardContainer, contract: &ContractOrder| {
+        let can_lay_down = !ContractBid::find_all(hand, contract).is_empty();
+        assert_eq!(contract_distance(hand, contract) == 0, can_lay_down, "{} for {}", hand, contract);
+    };
+    
+    check(&hand("J- J- J- J- 9C"), &contract("R:5"));
+    check(&hand("J- J- J- J- 5H 6H 7H 8H"), &contract("RR:8"));
+    check(&hand("J- J- J- J- 5H 6H 7H 9S"), &contract("RR:8"));
+    check(&hand("J- J- J- 2C 2C 2D"), &contract("GG:6"));
+    for contract in ["GG:10", "GR:10", "RR:12", "GGR:12", "RRR:12"].map(contract) {
+        for hand in random_hands(200, contract.hand_size()) {
+            check(&hand, &contract);
+        }
+    }
+}